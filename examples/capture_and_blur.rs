@@ -3,12 +3,11 @@ use std::path::PathBuf;
 
 use migl::attributes::GLType;
 use migl::buffer::BufferBld;
-use migl::frame::FrameBufferBuilder;
+use migl::frame::{AttachmentSpec, RenderTargetBuilder};
 use migl::math3d::Point;
 use migl::math3d::V2;
 use migl::program::DrawMode;
 use migl::texture::TexFormat;
-use migl::texture::Texture;
 use migl::uniform::Uniform;
 use migl::utils::camera::CylinderCamera;
 use migl::math3d::M44;
@@ -18,7 +17,7 @@ use migl::shader::Fragment;
 use migl::shader::Shader;
 use migl::shader::Vertex;
 
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
 use sdl2::video::GLProfile;
 
@@ -38,6 +37,7 @@ pub fn main() {
 
 	let window = video_subsystem.window("Stick people", WIDTH, HEIGHT)
 		.position_centered()
+		.resizable()
 		.opengl()
 		.build()
 		.unwrap();
@@ -76,21 +76,19 @@ pub fn main() {
 		.unwrap();
 
 
-	program.bind("position", buffer_vertices.direct_view()).unwrap();
-	program.bind("color",    buffer_colors.direct_view()).unwrap();
+	program.bind("position", buffer_vertices.direct_view().unwrap()).unwrap();
+	program.bind("color",    buffer_colors.direct_view().unwrap()).unwrap();
 
 
 	// -- CREATE FRAMEBUFFERS
-	let color_texture = Texture::allocate(WIDTH, HEIGHT, TexFormat::Rgb).unwrap();
-	let depth_texture = Texture::allocate(WIDTH, HEIGHT, TexFormat::Depth).unwrap();
-	let frame_buffer =
-		FrameBufferBuilder::new()
-		.attach_color(&color_texture)
-		.attach_depth(&depth_texture)
+	// follows the size of the window, the depth buffer is never sampled
+	let mut render_target =
+		RenderTargetBuilder::new(WIDTH, HEIGHT)
+		.color(AttachmentSpec::Texture(TexFormat::Rgb))
+		.depth(AttachmentSpec::Renderbuffer(TexFormat::Depth))
 		.build()
 		.unwrap();
 
-	gl.set_viewport(0, 0, WIDTH as i32, HEIGHT as i32);
 	gl.set_clear_color(0.0, 0.0, 0.0, 1.0);
 	// gl.set_line_width(5.0);
 
@@ -105,13 +103,13 @@ pub fn main() {
 		)
 		.build()
 		.unwrap();
-	blur_program.texture("scene_texture", color_texture.clone()).unwrap();
+	blur_program.texture("scene_texture", render_target.color_texture().unwrap().clone()).unwrap();
 	let position_buffer =
 		BufferBld::array()
 		.r#static()
 		.data(&[V2::new([-1., -1.]), V2::new([-1., 1.]), V2::new([1., -1.]), V2::new([1., 1.]),])
 		.unwrap();
-	blur_program.bind("position", position_buffer.direct_view()).unwrap();
+	blur_program.bind("position", position_buffer.direct_view().unwrap()).unwrap();
 
 
 
@@ -125,7 +123,7 @@ pub fn main() {
 
 	'main: loop {
 		let mut mvp = projection_matrix.dot(&camera.matrix());
-		mvp_uniform.pass(&mvp);
+		mvp_uniform.pass(&mvp).unwrap();
 
 		for event in event_pump.poll_iter() {
 			match event {
@@ -134,12 +132,12 @@ pub fn main() {
 					break 'main
 				},
 				Event::KeyDown { keycode: Some(Keycode::I), .. } => {
-					frame_buffer.make_current();
+					render_target.make_current();
 					gl.clear();
 
 					program.draw_buffer(DrawMode::Tris).unwrap();
 
-					color_texture
+					render_target.color_texture().unwrap()
 						.to_image(GLType::Ubyte)
 						.unwrap()
 						.save(&path)
@@ -152,6 +150,12 @@ pub fn main() {
 				Event::KeyDown { keycode: Some(Keycode::B), .. } => {
 					blur = !blur;
 				},
+				Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+					gl.set_viewport(0, 0, width, height);
+					render_target.resize(width as u32, height as u32).unwrap();
+					// a new texture was allocated
+					blur_program.texture("scene_texture", render_target.color_texture().unwrap().clone()).unwrap();
+				},
 				event => {
 					camera.control(&event, 10.);
 				},
@@ -159,7 +163,7 @@ pub fn main() {
 		}
 
 		if blur {
-			frame_buffer.make_current();
+			render_target.make_current();
 		}
 
 		gl.clear();
@@ -0,0 +1,108 @@
+use migl::math3d::{Point, V3};
+
+
+
+// the cube scene, shared by the GL & software examples
+
+pub const VERTICES : [V3; 6 * 3 * 2] = [
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([1.0, -1.0, 1.0]),
+	Point::<3>([1.0, -1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([1.0, 1.0, -1.0]),
+	Point::<3>([1.0, -1.0, -1.0]),
+
+	Point::<3>([-1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+	Point::<3>([-1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, 1.0, -1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, 1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([1.0, 1.0, -1.0]),
+	Point::<3>([-1.0, 1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, -1.0]),
+	Point::<3>([-1.0, 1.0, -1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, -1.0]),
+	Point::<3>([1.0, -1.0, -1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, 1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, 1.0]),
+
+	Point::<3>([1.0, 1.0, 1.0]),
+	Point::<3>([1.0, -1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, 1.0]),
+
+	Point::<3>([1.0, -1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, 1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+	Point::<3>([1.0, -1.0, 1.0]),
+	Point::<3>([1.0, -1.0, -1.0]),
+	Point::<3>([-1.0, -1.0, -1.0]),
+
+];
+
+
+pub const COLORS : [V3; 6 * 3 * 2] = [
+	Point::<3>([1.0, 0.0, 0.0]),
+	Point::<3>([1.0, 0.0, 0.0]),
+	Point::<3>([1.0, 0.0, 0.0]),
+
+	Point::<3>([1.0, 0.0, 0.0]),
+	Point::<3>([1.0, 0.0, 0.0]),
+	Point::<3>([1.0, 0.0, 0.0]),
+
+	Point::<3>([0.0, 1.0, 1.0]),
+	Point::<3>([0.0, 1.0, 1.0]),
+	Point::<3>([0.0, 1.0, 1.0]),
+
+	Point::<3>([0.0, 1.0, 1.0]),
+	Point::<3>([0.0, 1.0, 1.0]),
+	Point::<3>([0.0, 1.0, 1.0]),
+
+	Point::<3>([0.0, 1.0, 0.0]),
+	Point::<3>([0.0, 1.0, 0.0]),
+	Point::<3>([0.0, 1.0, 0.0]),
+
+	Point::<3>([0.0, 1.0, 0.0]),
+	Point::<3>([0.0, 1.0, 0.0]),
+	Point::<3>([0.0, 1.0, 0.0]),
+
+	Point::<3>([1.0, 1.0, 0.0]),
+	Point::<3>([1.0, 1.0, 0.0]),
+	Point::<3>([1.0, 1.0, 0.0]),
+
+	Point::<3>([1.0, 1.0, 0.0]),
+	Point::<3>([1.0, 1.0, 0.0]),
+	Point::<3>([1.0, 1.0, 0.0]),
+
+	Point::<3>([0.0, 0.0, 1.0]),
+	Point::<3>([0.0, 0.0, 1.0]),
+	Point::<3>([0.0, 0.0, 1.0]),
+
+	Point::<3>([0.0, 0.0, 1.0]),
+	Point::<3>([0.0, 0.0, 1.0]),
+	Point::<3>([0.0, 0.0, 1.0]),
+
+	Point::<3>([1.0, 0.0, 1.0]),
+	Point::<3>([1.0, 0.0, 1.0]),
+	Point::<3>([1.0, 0.0, 1.0]),
+
+	Point::<3>([1.0, 0.0, 1.0]),
+	Point::<3>([1.0, 0.0, 1.0]),
+	Point::<3>([1.0, 0.0, 1.0]),
+
+];
\ No newline at end of file
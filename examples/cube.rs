@@ -1,5 +1,4 @@
 use migl::buffer::BufferBld;
-use migl::math3d::Point;
 use migl::uniform::Uniform;
 use migl::utils::camera::CylinderCamera;
 use migl::math3d::M44;
@@ -15,6 +14,10 @@ use sdl2::video::GLProfile;
 
 use migl::*;
 
+#[path = "common/cube.rs"]
+mod cube;
+use cube::{COLORS, VERTICES};
+
 const WIDTH  : u32 = 800;
 const HEIGHT : u32 = 600;
 
@@ -66,8 +69,8 @@ pub fn main() {
 		.unwrap();
 
 
-	program.bind("position", buffer_vertices.direct_view()).unwrap();
-	program.bind("color",    buffer_colors.direct_view()).unwrap();
+	program.bind("position", buffer_vertices.direct_view().unwrap()).unwrap();
+	program.bind("color",    buffer_colors.direct_view().unwrap()).unwrap();
 
 
 	// 2nd organization : (PPPCCC)
@@ -82,8 +85,8 @@ pub fn main() {
 
 	let n_vertices = VERTICES.len();
 	let program2 = program.duplicate().unwrap();
-	program2.bind("position", buffer_vertices_colors_juxtaposed.view_range(0  .. n_vertices)).unwrap();
-	program2.bind("color",    buffer_vertices_colors_juxtaposed.view_range(n_vertices .. 2 * n_vertices)).unwrap();
+	program2.bind("position", buffer_vertices_colors_juxtaposed.view_range(0  .. n_vertices).unwrap()).unwrap();
+	program2.bind("color",    buffer_vertices_colors_juxtaposed.view_range(n_vertices .. 2 * n_vertices).unwrap()).unwrap();
 
 	// 2nd organization : (PCPCPC)
 	struct ColoredVertex {
@@ -104,8 +107,8 @@ pub fn main() {
 	;
 
 	let program3 = program.duplicate().unwrap();
-	program3.bind("position", buffer_vertices_colors_interspersed.view(field!(position))).unwrap();
-	program3.bind("color",    buffer_vertices_colors_interspersed.view(field!(color))).unwrap();
+	program3.bind("position", buffer_vertices_colors_interspersed.view(field!(ColoredVertex, position)).unwrap()).unwrap();
+	program3.bind("color",    buffer_vertices_colors_interspersed.view(field!(ColoredVertex, color)).unwrap()).unwrap();
 
 
 	let mut camera = CylinderCamera::new();
@@ -130,127 +133,19 @@ pub fn main() {
 		gl.clear();
 
 		let mut mvp = projection_matrix.dot(&camera.matrix());
-		mvp_uniform.pass(&mvp);
+		mvp_uniform.pass(&mvp).unwrap();
 		program.draw_buffer(program::DrawMode::Tris).unwrap();
 
 		mvp = mvp.dot(&M44::translation(V3::new([1., 2., 3.])));
-		mvp_uniform.pass(&mvp);
+		mvp_uniform.pass(&mvp).unwrap();
 		program2.draw_buffer(program::DrawMode::Tris).unwrap();
 
 
 		mvp = mvp.dot(&M44::translation(V3::new([-1.5, 0., -1.])));
-		mvp_uniform.pass(&mvp);
+		mvp_uniform.pass(&mvp).unwrap();
 		program3.draw_buffer(program::DrawMode::Tris).unwrap();
 
 
 		window.gl_swap_window();
 	}
 }
-
-
-
-
-
-
-const VERTICES : [V3; 6 * 3 * 2] = [
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([1.0, -1.0, 1.0]),
-	Point::<3>([1.0, -1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([1.0, 1.0, -1.0]),
-	Point::<3>([1.0, -1.0, -1.0]),
-
-	Point::<3>([-1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-	Point::<3>([-1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, 1.0, -1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, 1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([1.0, 1.0, -1.0]),
-	Point::<3>([-1.0, 1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, -1.0]),
-	Point::<3>([-1.0, 1.0, -1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, -1.0]),
-	Point::<3>([1.0, -1.0, -1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, 1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, 1.0]),
-
-	Point::<3>([1.0, 1.0, 1.0]),
-	Point::<3>([1.0, -1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, 1.0]),
-
-	Point::<3>([1.0, -1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, 1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-	Point::<3>([1.0, -1.0, 1.0]),
-	Point::<3>([1.0, -1.0, -1.0]),
-	Point::<3>([-1.0, -1.0, -1.0]),
-
-];
-
-
-const COLORS : [V3; 6 * 3 * 2] = [
-	Point::<3>([1.0, 0.0, 0.0]),
-	Point::<3>([1.0, 0.0, 0.0]),
-	Point::<3>([1.0, 0.0, 0.0]),
-
-	Point::<3>([1.0, 0.0, 0.0]),
-	Point::<3>([1.0, 0.0, 0.0]),
-	Point::<3>([1.0, 0.0, 0.0]),
-
-	Point::<3>([0.0, 1.0, 1.0]),
-	Point::<3>([0.0, 1.0, 1.0]),
-	Point::<3>([0.0, 1.0, 1.0]),
-
-	Point::<3>([0.0, 1.0, 1.0]),
-	Point::<3>([0.0, 1.0, 1.0]),
-	Point::<3>([0.0, 1.0, 1.0]),
-
-	Point::<3>([0.0, 1.0, 0.0]),
-	Point::<3>([0.0, 1.0, 0.0]),
-	Point::<3>([0.0, 1.0, 0.0]),
-
-	Point::<3>([0.0, 1.0, 0.0]),
-	Point::<3>([0.0, 1.0, 0.0]),
-	Point::<3>([0.0, 1.0, 0.0]),
-
-	Point::<3>([1.0, 1.0, 0.0]),
-	Point::<3>([1.0, 1.0, 0.0]),
-	Point::<3>([1.0, 1.0, 0.0]),
-
-	Point::<3>([1.0, 1.0, 0.0]),
-	Point::<3>([1.0, 1.0, 0.0]),
-	Point::<3>([1.0, 1.0, 0.0]),
-
-	Point::<3>([0.0, 0.0, 1.0]),
-	Point::<3>([0.0, 0.0, 1.0]),
-	Point::<3>([0.0, 0.0, 1.0]),
-
-	Point::<3>([0.0, 0.0, 1.0]),
-	Point::<3>([0.0, 0.0, 1.0]),
-	Point::<3>([0.0, 0.0, 1.0]),
-
-	Point::<3>([1.0, 0.0, 1.0]),
-	Point::<3>([1.0, 0.0, 1.0]),
-	Point::<3>([1.0, 0.0, 1.0]),
-
-	Point::<3>([1.0, 0.0, 1.0]),
-	Point::<3>([1.0, 0.0, 1.0]),
-	Point::<3>([1.0, 0.0, 1.0]),
-
-];
\ No newline at end of file
@@ -72,7 +72,7 @@ pub fn main() {
 		.dynamic()
 		.allocate(6 * 6 * Blob::MAX_VOXELS ) // 100 voxel limit
 		.unwrap();
-	buffer.pass_data(&blob.gen_faces());
+	buffer.pass_data(&blob.gen_faces()).unwrap();
 
 
 	// -- create program
@@ -84,15 +84,15 @@ pub fn main() {
 		.build()
 		.unwrap();
 
-	program.bind("position", buffer.view(field!(position))).unwrap();
-	program.bind("normal",   buffer.view(field!(normal))).unwrap();
+	program.bind("position", buffer.view(field!(VertexNormal, position)).unwrap()).unwrap();
+	program.bind("normal",   buffer.view(field!(VertexNormal, normal)).unwrap()).unwrap();
 
-	program.uniform("projection"       ).unwrap().pass(&projection_matrix);
-	program.uniform("ambient_strength" ).unwrap().pass(&0.3);
-	program.uniform("specular_strength").unwrap().pass(&0.3);
-	program.uniform("diffuse_strength" ).unwrap().pass(&1.0);
-	program.uniform("light_strength"   ).unwrap().pass(&0.8);
-	program.uniform("light_direction"  ).unwrap().pass(&(V3::new([1.0, 1.0, 1.0]).normalize()));
+	program.uniform("projection"       ).unwrap().pass(&projection_matrix).unwrap();
+	program.uniform("ambient_strength" ).unwrap().pass(&0.3).unwrap();
+	program.uniform("specular_strength").unwrap().pass(&0.3).unwrap();
+	program.uniform("diffuse_strength" ).unwrap().pass(&1.0).unwrap();
+	program.uniform("light_strength"   ).unwrap().pass(&0.8).unwrap();
+	program.uniform("light_direction"  ).unwrap().pass(&(V3::new([1.0, 1.0, 1.0]).normalize())).unwrap();
 	let mv_uniform = program.uniform("model_view").unwrap();
 	let camera_pos_uniform = program.uniform("camera_pos").unwrap();
 
@@ -114,9 +114,9 @@ pub fn main() {
 				},
 				Event::KeyDown { keycode: Some(Keycode::I), .. } => {
 					blob.add_random(&mut rng);
-					buffer.pass_data(&blob.gen_faces());
-					program.bind("position", buffer.view(field!(position))).unwrap();
-					program.bind("normal",   buffer.view(field!(normal))).unwrap();
+					buffer.pass_data(&blob.gen_faces()).unwrap();
+					program.bind("position", buffer.view(field!(VertexNormal, position)).unwrap()).unwrap();
+					program.bind("normal",   buffer.view(field!(VertexNormal, normal)).unwrap()).unwrap();
 				},
 				event => {
 					camera.control(&event, 10.);
@@ -127,9 +127,9 @@ pub fn main() {
 		gl.clear();
 
 		let view_matrix = camera.matrix();
-		mv_uniform.pass(&view_matrix);
+		mv_uniform.pass(&view_matrix).unwrap();
 		let camera_pos = camera.position();
-		camera_pos_uniform.pass(&camera_pos);
+		camera_pos_uniform.pass(&camera_pos).unwrap();
 
 		program.draw_buffer(DrawMode::Tris).unwrap();
 
@@ -6,7 +6,7 @@ use migl::buffer::BufferBld;
 use migl::program::DrawMode;
 use migl::texture::TexFormat;
 use migl::utils::camera::CylinderCamera;
-use migl::utils::load::ObjLoader;
+use migl::utils::load::{ObjLoader, VertexNormal};
 use migl::math3d::M44;
 use migl::math3d::V3;
 use migl::program::ProgramBuilder;
@@ -67,9 +67,9 @@ pub fn main() {
 		.into_vertex_normals();
 	let jug_buffer = BufferBld::array().data(&jug_data).unwrap();
 
-	jug_program.bind("position",   jug_buffer.view(field!(vertex))).unwrap();
-	jug_program.bind("normal",     jug_buffer.view(field!(normal))).unwrap();
-	jug_program.bind("tex_coords", jug_buffer.view(field!(tex_coords))).unwrap();
+	jug_program.bind("position",   jug_buffer.view(field!(VertexNormal, vertex)).unwrap()).unwrap();
+	jug_program.bind("normal",     jug_buffer.view(field!(VertexNormal, normal)).unwrap()).unwrap();
+	jug_program.bind("tex_coords", jug_buffer.view(field!(VertexNormal, tex_coords)).unwrap()).unwrap();
 
 	// textures
 	let file = std::io::BufReader::new(std::fs::File::open("resources/model/jug/textures/diffuse.jpg").unwrap());
@@ -85,11 +85,11 @@ pub fn main() {
 
 
 	// Creating uniform
-	jug_program.uniform("ambient_strength").unwrap().pass(&0.3);
-	jug_program.uniform("specular_strength").unwrap().pass(&0.3);
-	jug_program.uniform("diffuse_strength").unwrap().pass(&1.0);
-	jug_program.uniform("light_strength").unwrap().pass(&0.8);
-	jug_program.uniform("light_direction").unwrap().pass(&(V3::new([1.0, -1.0, 1.0]).normalize()));
+	jug_program.uniform("ambient_strength").unwrap().pass(&0.3).unwrap();
+	jug_program.uniform("specular_strength").unwrap().pass(&0.3).unwrap();
+	jug_program.uniform("diffuse_strength").unwrap().pass(&1.0).unwrap();
+	jug_program.uniform("light_strength").unwrap().pass(&0.8).unwrap();
+	jug_program.uniform("light_direction").unwrap().pass(&(V3::new([1.0, -1.0, 1.0]).normalize())).unwrap();
 	let camera_pos_uniform = jug_program.uniform("camera_pos").unwrap();
 
 
@@ -127,9 +127,9 @@ pub fn main() {
 		let view_matrix = camera.matrix();
 		let mv_matrix = view_matrix.dot(&model_matrix);
 
-		mv_uniform.pass(&mv_matrix);
-		p_uniform.pass(&projection_matrix);
-		camera_pos_uniform.pass(&camera.position());
+		mv_uniform.pass(&mv_matrix).unwrap();
+		p_uniform.pass(&projection_matrix).unwrap();
+		camera_pos_uniform.pass(&camera.position()).unwrap();
 
 		jug_program.set_current();
 		jug_program.draw_buffer(DrawMode::Tris).unwrap();
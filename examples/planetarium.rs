@@ -72,16 +72,16 @@ pub fn main() {
 		BufferBld::array()
 		.data(&star_data)
 		.unwrap();
-	star_program.bind("position",      star_positions.view(field!(direction))).unwrap();
-	star_program.bind("constellation", star_positions.view(field!(constellation))).unwrap();
-	star_program.bind("magnitude",     star_positions.view(field!(magnitude))).unwrap();
+	star_program.bind("position",      star_positions.view(field!(StarVertex, direction)).unwrap()).unwrap();
+	star_program.bind("constellation", star_positions.view(field!(StarVertex, constellation)).unwrap()).unwrap();
+	star_program.bind("magnitude",     star_positions.view(field!(StarVertex, magnitude)).unwrap()).unwrap();
 	let max_magnitude = star_data.iter().map(|s| s.magnitude).max_by(f32::total_cmp).unwrap();
-	star_program.uniform("max_magnitude").unwrap().pass(&max_magnitude);
+	star_program.uniform("max_magnitude").unwrap().pass(&max_magnitude).unwrap();
 	let min_magnitude = star_data.iter().map(|s| s.magnitude).min_by(f32::total_cmp).unwrap();
-	star_program.uniform("min_magnitude").unwrap().pass(&min_magnitude);
+	star_program.uniform("min_magnitude").unwrap().pass(&min_magnitude).unwrap();
 	let constellation_index_uniform = star_program.uniform::<gl::types::GLuint>("current_constellation").unwrap();
 	let mut current_constellation_index : gl::types::GLuint = 0;
-	constellation_index_uniform.pass(&0);
+	constellation_index_uniform.pass(&0).unwrap();
 	let vp_uniform = star_program.uniform::<M44>("view_projection").unwrap();
 
 
@@ -107,7 +107,7 @@ pub fn main() {
 					break 'main
 				},
 				Event::KeyDown { keycode: Some(Keycode::C), .. } => {
-					constellation_index_uniform.pass(&(constellations.len() as gl::types::GLuint));
+					constellation_index_uniform.pass(&(constellations.len() as gl::types::GLuint)).unwrap();
 				},
 				Event::KeyDown { keycode, .. } => {
 					let changed = match keycode {
@@ -121,7 +121,7 @@ pub fn main() {
 						println!("current constellation: {}", constellations[i]);
 						let pos = constellation_pos[i];
 						camera = TurntableCamera::new(V3::ZERO, pos.scale(- camera.pos_camera().norm()), V3::E_Y);
-						constellation_index_uniform.pass(&current_constellation_index);
+						constellation_index_uniform.pass(&current_constellation_index).unwrap();
 					}
 				},
 				Event::MouseButtonDown { mouse_btn : MouseButton::Middle, x, y, .. } => {
@@ -148,7 +148,7 @@ pub fn main() {
 
 
 		let vp_matrix = projection_matrix.dot(&view_matrix);
-		vp_uniform.pass(&vp_matrix);
+		vp_uniform.pass(&vp_matrix).unwrap();
 		star_program.draw_buffer(DrawMode::Points).unwrap();
 
 
@@ -0,0 +1,57 @@
+use migl::math3d::{M44, V3, V4};
+use migl::program::DrawMode;
+use migl::software::{Buffer, FrameBuffer, Program};
+use migl::utils::camera::CylinderCamera;
+
+#[path = "common/cube.rs"]
+mod cube;
+use cube::{COLORS, VERTICES};
+
+const WIDTH  : u32 = 800;
+const HEIGHT : u32 = 600;
+
+// the scene of the "cube" example, rendered on the CPU into "software_cube.png"
+// no window nor driver needed
+
+#[derive(Clone, Copy)]
+struct ColoredVertex {
+	position : V3,
+	color    : V3,
+}
+
+// counterpart of "resources/shaders/cube"
+fn cube_program<'a>(mvp : M44) -> Program<'a, ColoredVertex, V3> {
+	Program::new(
+		move |vertex : &ColoredVertex| (mvp.apply(&vertex.position.homo()), vertex.color),
+		|color : &V3| V4::new([color.0[0], color.0[1], color.0[2], 1.0]),
+	)
+}
+
+pub fn main() {
+	let data = VERTICES
+		.iter()
+		.zip(COLORS.iter())
+		.map(|(position, color)| ColoredVertex { position : *position, color : *color })
+		.collect::<Vec<_>>();
+	let vertices = Buffer::from_data(&data);
+
+	let camera = CylinderCamera::new();
+	let projection_matrix = M44::perspective_projection(0.1, 50., 60., 1.);
+
+	let mut target = FrameBuffer::new(WIDTH, HEIGHT);
+	target.clear(V4::new([0.0, 0.0, 0.0, 1.0]));
+
+	// same placements as the three programs of the "cube" example
+	let mut mvp = projection_matrix.dot(&camera.matrix());
+	cube_program(mvp).draw_buffer(&mut target, &vertices, DrawMode::Tris).unwrap();
+
+	mvp = mvp.dot(&M44::translation(V3::new([1., 2., 3.])));
+	cube_program(mvp).draw_buffer(&mut target, &vertices, DrawMode::Tris).unwrap();
+
+	mvp = mvp.dot(&M44::translation(V3::new([-1.5, 0., -1.])));
+	cube_program(mvp).draw_buffer(&mut target, &vertices, DrawMode::Tris).unwrap();
+
+	// bottom row first, like images read back from GL, so that both can be diffed
+	target.to_image().save("software_cube.png").unwrap();
+	println!("Saved file to software_cube.png");
+}
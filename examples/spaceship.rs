@@ -2,7 +2,7 @@ use std::path::Path;
 
 use migl::buffer::BufferBld;
 use migl::utils::camera::CylinderCamera;
-use migl::utils::load::ObjLoader;
+use migl::utils::load::{ObjLoader, VertexNormal};
 use migl::math3d::M44;
 use migl::math3d::V3;
 use migl::program::ProgramBuilder;
@@ -64,13 +64,13 @@ pub fn main() {
 		.into_vertex_normals();
 	let spaceship_buffer = BufferBld::array().data(&spaceship_data).unwrap();
 
-	program.bind("position", spaceship_buffer.view(field!(vertex))).unwrap();
-	program.bind("normal",   spaceship_buffer.view(field!(normal))).unwrap();
+	program.bind("position", spaceship_buffer.view(field!(VertexNormal, vertex)).unwrap()).unwrap();
+	program.bind("normal",   spaceship_buffer.view(field!(VertexNormal, normal)).unwrap()).unwrap();
 
-	program.uniform("ambient_strength").unwrap().pass(&0.1);
-	program.uniform("specular_strength").unwrap().pass(&0.2);
-	program.uniform("diffuse_strength").unwrap().pass(&0.6);
-	program.uniform("light_strength").unwrap().pass(&0.5);
+	program.uniform("ambient_strength").unwrap().pass(&0.1).unwrap();
+	program.uniform("specular_strength").unwrap().pass(&0.2).unwrap();
+	program.uniform("diffuse_strength").unwrap().pass(&0.6).unwrap();
+	program.uniform("light_strength").unwrap().pass(&0.5).unwrap();
 	let light_dir_uniform  = program.uniform("light_direction").unwrap();
 	let camera_pos_uniform = program.uniform("camera_pos").unwrap();
 
@@ -89,7 +89,7 @@ pub fn main() {
 		BufferBld::array()
 		.data(&[V3::ZERO])
 		.unwrap();
-	ray_program.bind("pos", ray_buffer.direct_view()).unwrap();
+	ray_program.bind("pos", ray_buffer.direct_view().unwrap()).unwrap();
 	let raydir_uniform = ray_program.uniform("ray_dir").unwrap();
 	let ray_vp_uniform = ray_program.uniform("view_projection").unwrap();
 
@@ -148,10 +148,10 @@ pub fn main() {
 		// LIGHT that comes from above has negative Y coordinate
 		let light_dir = model_rotation.apply(&(V3::new([light_time.cos() * width, -HEIGHT, light_time.sin() * width]))).normalize();
 		// let light_dir = model_rotation.apply(&(V3::new([-1.0, -0.0, -1.0]))).normalize();
-		light_dir_uniform.pass(&light_dir);
-		mv_uniform.pass(&mv_matrix);
-		p_uniform.pass(&projection_matrix);
-		camera_pos_uniform.pass(&camera.position());
+		light_dir_uniform.pass(&light_dir).unwrap();
+		mv_uniform.pass(&mv_matrix).unwrap();
+		p_uniform.pass(&projection_matrix).unwrap();
+		camera_pos_uniform.pass(&camera.position()).unwrap();
 
 		program.set_current();
 		program.draw_buffer(program::DrawMode::Tris).unwrap();
@@ -159,8 +159,8 @@ pub fn main() {
 		// axes.draw(&vp_matrix).unwrap();
 
 		// -- Draw rays
-		raydir_uniform.pass(&light_dir);
-		ray_vp_uniform.pass(&vp_matrix);
+		raydir_uniform.pass(&light_dir).unwrap();
+		ray_vp_uniform.pass(&vp_matrix).unwrap();
 
 		ray_program.draw_buffer(program::DrawMode::Points).unwrap();
 
@@ -15,6 +15,7 @@ use migl::shader::Shader;
 use migl::shader::Vertex;
 
 use migl::utils::camera::TurntableCamera;
+use migl::profiler::GPUProfiler;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseButton;
@@ -73,8 +74,8 @@ pub fn main() {
 		.unwrap();
 
 
-	program.bind("position", buffer_vertices.direct_view()).unwrap();
-	program.bind("color",    buffer_colors.direct_view()).unwrap();
+	program.bind("position", buffer_vertices.direct_view().unwrap()).unwrap();
+	program.bind("color",    buffer_colors.direct_view().unwrap()).unwrap();
 
 
 
@@ -91,7 +92,7 @@ pub fn main() {
 		BufferBld::array()
 		.data(&load_stars(Path::new("resources/stars.json")))
 		.unwrap();
-	star_program.bind("position", star_positions.direct_view()).unwrap();
+	star_program.bind("position", star_positions.direct_view().unwrap()).unwrap();
 	let vp_uniform = star_program.uniform::<M44>("view_projection").unwrap();
 
 
@@ -110,6 +111,8 @@ pub fn main() {
 
 
 
+	let mut profiler = GPUProfiler::new();
+
 	'main: loop {
 		// let mut elapsed = timer.elapsed().as_secs_f32();
 
@@ -133,6 +136,13 @@ pub fn main() {
 						else               { -1.0 };
 					camera.zoom(factor);
 				},
+				Event::KeyDown { keycode: Some(Keycode::T), .. } => {
+					if let Some(report) = profiler.last_report() {
+						for (label, duration) in report {
+							println!("{label:>8}: {duration:?}");
+						}
+					}
+				},
 				_ => {}
 			}
 		}
@@ -141,13 +151,14 @@ pub fn main() {
 		gl.clear();
 
 		let vp = projection_matrix.dot(camera.matrix());
-		mvp_uniform.pass(&vp);
-		program.draw_buffer(program::DrawMode::Tris).unwrap();
+		mvp_uniform.pass(&vp).unwrap();
+		profiler.section("model", || program.draw_buffer(program::DrawMode::Tris)).unwrap().unwrap();
 
-		vp_uniform.pass(&vp);
-		star_program.draw_buffer(program::DrawMode::Points).unwrap();
+		vp_uniform.pass(&vp).unwrap();
+		profiler.section("stars", || star_program.draw_buffer(program::DrawMode::Points)).unwrap().unwrap();
 
-		axes.draw(&vp).unwrap();
+		profiler.section("axes", || axes.draw(&vp)).unwrap().unwrap();
+		profiler.end_frame();
 
 		window.gl_swap_window();
 	}
@@ -1,6 +1,6 @@
 use crate::math3d::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GLType {
 	Int,
 	Uint,
@@ -35,6 +35,18 @@ impl GLType {
 			Self::Float  => gl::FLOAT, 
 		}
 	}
+
+	pub fn size(&self) -> usize {
+		match self {
+			Self::Int    => std::mem::size_of::<gl::types::GLint>(),
+			Self::Uint   => std::mem::size_of::<gl::types::GLuint>(),
+			Self::Byte   => std::mem::size_of::<gl::types::GLbyte>(),
+			Self::Ubyte  => std::mem::size_of::<gl::types::GLubyte>(),
+			Self::Short  => std::mem::size_of::<gl::types::GLshort>(),
+			Self::Ushort => std::mem::size_of::<gl::types::GLushort>(),
+			Self::Float  => std::mem::size_of::<gl::types::GLfloat>(),
+		}
+	}
 }
 
 
@@ -43,12 +55,19 @@ impl GLType {
 
 // Type info for GPU
 // describes an array of "n_components" elements of type "gl_type"'s
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GPUInfo {
 	pub n_components: usize,
 	pub gl_type:      GLType,
 }
 
+impl GPUInfo {
+	// size in bytes of one element
+	pub fn size(&self) -> usize {
+		self.n_components * self.gl_type.size()
+	}
+}
+
 
 
 
@@ -3,9 +3,12 @@ use std::ops::Range;
 use gl::types::*;
 
 use crate::attributes::*;
+use crate::debug;
+use crate::dsa;
 use crate::error::*;
 use crate::*;
-use crate::program::AttributePos;
+use crate::sync::Fence;
+use crate::mapping::*;
 
 
 
@@ -24,30 +27,68 @@ pub enum BufferKind {
 	ArrayBuffer,
 	IndexBuffer,
 	UniformBuffer,
+	DrawIndirect,
+	DispatchIndirect,
+	TransformFeedback,
+	PixelPack,
+	PixelUnpack,
+	CopyRead,
+	CopyWrite,
+	TextureBuffer,
+	ShaderStorage,
 }
 
 
 impl BufferKind {
 	pub fn cst(&self) -> gl::types::GLenum {
 		match self {
-			Self::ArrayBuffer   => gl::ARRAY_BUFFER,
-			Self::IndexBuffer   => gl::ELEMENT_ARRAY_BUFFER,
-			Self::UniformBuffer => gl::UNIFORM_BUFFER,
+			Self::ArrayBuffer       => gl::ARRAY_BUFFER,
+			Self::IndexBuffer       => gl::ELEMENT_ARRAY_BUFFER,
+			Self::UniformBuffer     => gl::UNIFORM_BUFFER,
+			Self::DrawIndirect      => gl::DRAW_INDIRECT_BUFFER,
+			Self::DispatchIndirect  => gl::DISPATCH_INDIRECT_BUFFER,
+			Self::TransformFeedback => gl::TRANSFORM_FEEDBACK_BUFFER,
+			Self::PixelPack         => gl::PIXEL_PACK_BUFFER,
+			Self::PixelUnpack       => gl::PIXEL_UNPACK_BUFFER,
+			Self::CopyRead          => gl::COPY_READ_BUFFER,
+			Self::CopyWrite         => gl::COPY_WRITE_BUFFER,
+			Self::TextureBuffer     => gl::TEXTURE_BUFFER,
+			Self::ShaderStorage     => gl::SHADER_STORAGE_BUFFER,
 		}
 	}
 }
 
-#[derive(Debug)]
+// how often the data is updated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpdateKind {
+	Stream,
 	Dynamic,
 	Static,
 }
 
+// who writes and who reads the data:
+// "Draw": written by the CPU, read by GL
+// "Read": written by GL, read back by the CPU
+// "Copy": written and read by GL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+	Draw,
+	Read,
+	Copy,
+}
+
 impl UpdateKind {
-	pub fn cst(&self) -> gl::types::GLenum {
-		match self {
-			Self::Static  => gl::STATIC_DRAW,
-			Self::Dynamic => gl::DYNAMIC_DRAW,
+	pub fn cst(&self, access : AccessKind) -> gl::types::GLenum {
+		match (self, access) {
+			(Self::Stream,  AccessKind::Draw) => gl::STREAM_DRAW,
+			(Self::Stream,  AccessKind::Read) => gl::STREAM_READ,
+			(Self::Stream,  AccessKind::Copy) => gl::STREAM_COPY,
+			(Self::Static,  AccessKind::Draw) => gl::STATIC_DRAW,
+			(Self::Static,  AccessKind::Read) => gl::STATIC_READ,
+			(Self::Static,  AccessKind::Copy) => gl::STATIC_COPY,
+			(Self::Dynamic, AccessKind::Draw) => gl::DYNAMIC_DRAW,
+			(Self::Dynamic, AccessKind::Read) => gl::DYNAMIC_READ,
+			(Self::Dynamic, AccessKind::Copy) => gl::DYNAMIC_COPY,
 		}
 	}
 }
@@ -56,32 +97,34 @@ impl UpdateKind {
 pub struct BufferBld {
 	pub kind    : BufferKind,
 	pub update  : UpdateKind,
+	pub access  : AccessKind,
+	// shown by debugging tools, see "debug"
+	pub label   : Option<String>,
 }
 
 impl BufferBld {
-	pub fn array() -> Self
+	pub fn new(kind : BufferKind) -> Self
 	{ 
 		Self {
-			kind   : BufferKind::ArrayBuffer,
+			kind,
 			update : UpdateKind::Static,
+			access : AccessKind::Draw,
+			label  : None,
 		} 
 	}
 
-	pub fn indices() -> Self
-	{ 
-		Self {
-			kind   : BufferKind::IndexBuffer,
-			update : UpdateKind::Static,
-		}
-	}
-
-	pub fn uniform() -> Self
-	{ 
-		Self {
-			kind   : BufferKind::UniformBuffer,
-			update : UpdateKind::Static,
-		}
-	}
+	pub fn array()              -> Self { Self::new(BufferKind::ArrayBuffer)       }
+	pub fn indices()            -> Self { Self::new(BufferKind::IndexBuffer)       }
+	pub fn uniform()            -> Self { Self::new(BufferKind::UniformBuffer)     }
+	pub fn draw_indirect()      -> Self { Self::new(BufferKind::DrawIndirect)      }
+	pub fn dispatch_indirect()  -> Self { Self::new(BufferKind::DispatchIndirect)  }
+	pub fn transform_feedback() -> Self { Self::new(BufferKind::TransformFeedback) }
+	pub fn pixel_pack()         -> Self { Self::new(BufferKind::PixelPack)         }
+	pub fn pixel_unpack()       -> Self { Self::new(BufferKind::PixelUnpack)       }
+	pub fn copy_read()          -> Self { Self::new(BufferKind::CopyRead)          }
+	pub fn copy_write()         -> Self { Self::new(BufferKind::CopyWrite)         }
+	pub fn texture_buffer()     -> Self { Self::new(BufferKind::TextureBuffer)     }
+	pub fn shader_storage()     -> Self { Self::new(BufferKind::ShaderStorage)     }
 
 	pub fn r#static(mut self) -> Self {
 		self.update = UpdateKind::Static;
@@ -93,24 +136,54 @@ impl BufferBld {
 		self
 	}
 
+	pub fn stream(mut self) -> Self {
+		self.update = UpdateKind::Stream;
+		self
+	}
+
+	pub fn for_draw(mut self) -> Self {
+		self.access = AccessKind::Draw;
+		self
+	}
+
+	pub fn for_read(mut self) -> Self {
+		self.access = AccessKind::Read;
+		self
+	}
+
+	pub fn for_copy(mut self) -> Self {
+		self.access = AccessKind::Copy;
+		self
+	}
+
+	pub fn label(mut self, label : &str) -> Self {
+		self.label = Some(label.to_string());
+		self
+	}
+
+	#[track_caller]
 	pub fn data<A>(self, data : &[A]) -> Result<Buffer<A>, GLError> {
 		let raw_buffer = self.data_raw(data)?;
 		Ok(Buffer {
 			raw: raw_buffer,
 			n_elems: data.len(),
+			capacity: data.len(),
 			_phantom: std::marker::PhantomData,
 		})
 	}
 
+	#[track_caller]
 	pub fn allocate<A>(self, n_elems : usize) -> Result<Buffer<A>, GLError> {
 		let raw_buffer = self.allocate_raw(n_elems * std::mem::size_of::<A>())?;
 		Ok(Buffer {
 			raw: raw_buffer,
 			n_elems : 0,
+			capacity: n_elems,
 			_phantom: std::marker::PhantomData,
 		})
 	}
 
+	#[track_caller]
 	pub fn data_any<A>(self, data : &[A], n_elems : usize, gpu_info : GPUInfo) -> Result<AnyBuffer, GLError>
 	{
 		let raw_buffer = self.data_raw(data)?;
@@ -121,14 +194,44 @@ impl BufferBld {
 		})
 	}
 
+	#[track_caller]
 	pub fn data_raw<A>(self, data : &[A]) -> Result<RawBuffer, GLError>
 	{
-		RawBuffer::from_data(self.update, self.kind, data)
+		let raw_buffer = RawBuffer::from_data(self.update, self.access, self.kind, data)?;
+		self.set_label(raw_buffer.id);
+		Ok(raw_buffer)
 	}
 
+	#[track_caller]
 	pub fn allocate_raw(self, n : usize) -> Result<RawBuffer, GLError>
 	{
-		RawBuffer::from_null(self.update, self.kind, n)
+		let raw_buffer = RawBuffer::from_null(self.update, self.access, self.kind, n)?;
+		self.set_label(raw_buffer.id);
+		Ok(raw_buffer)
+	}
+
+	// immutable storage, mapped for writing for as long as the buffer lives (requires GL 4.4)
+	// the update hint is irrelevant here
+	#[track_caller]
+	pub fn allocate_persistent<A : bytemuck::Pod>(self, n_elems : usize) -> Result<PersistentBuffer<A>, GLError>
+	{
+		let buffer = PersistentBuffer::new(self.kind, n_elems)?;
+		self.set_label(buffer.id());
+		Ok(buffer)
+	}
+
+	#[track_caller]
+	pub fn stream_ring<A : bytemuck::Pod>(self, region_len : usize, n_regions : usize) -> Result<StreamRing<A>, GLError>
+	{
+		let ring = StreamRing::new(self.kind, region_len, n_regions)?;
+		self.set_label(ring.buffer().id());
+		Ok(ring)
+	}
+
+	fn set_label(&self, id : BufferId) {
+		if let Some(label) = &self.label {
+			debug::set_label(gl::BUFFER, id.0, label);
+		}
 	}
 }
 
@@ -136,92 +239,129 @@ impl BufferBld {
 pub struct RawBuffer {
 	pub id      : BufferId,
 	pub kind    : BufferKind,
+	pub update  : UpdateKind,
+	pub access  : AccessKind,
 }
 
 impl RawBuffer {
+	#[track_caller]
 	fn from_data<A>(
 		update  : UpdateKind,
+		access  : AccessKind,
 		kind    : BufferKind,
 		data    : &[A],
 	) -> Result<Self, GLError>
 	{
-		let mut buffer_id = 0;
-		unsafe {
-			gl::GenBuffers(1, &mut buffer_id);
-		}
+		let buffer_id = dsa::create_buffer();
 		if buffer_id == 0 {
 			return Err(GLError::CouldNotCreateBuffer);
 		}
 
 		unsafe {
-			gl::BindBuffer(kind.cst(), buffer_id)
-		}
-
-		unsafe {
-			gl::BufferData(
+			dsa::buffer_data(
 				kind.cst(),
-				std::mem::size_of_val(data) as gl::types::GLsizeiptr,
+				buffer_id,
+				std::mem::size_of_val(data),
 				data.as_ptr().cast(),
-				update.cst(),
+				update.cst(access),
 			)
 		}
-		unsafe {
-			gl::BindBuffer(kind.cst(), 0)
-		}
+		check("RawBuffer::from_data")?;
 		Ok(RawBuffer {
 			id: BufferId(buffer_id),
 			kind,
+			update,
+			access,
 		})
 	}
 
+	#[track_caller]
 	fn from_null(
 		update  : UpdateKind,
+		access  : AccessKind,
 		kind    : BufferKind,
 		size    : usize,
 	) -> Result<Self, GLError>
 	{
-		let mut buffer_id = 0;
-		unsafe {
-			gl::GenBuffers(1, &mut buffer_id);
-		}
+		let buffer_id = dsa::create_buffer();
 		if buffer_id == 0 {
 			return Err(GLError::CouldNotCreateBuffer);
 		}
 
 		unsafe {
-			gl::BindBuffer(kind.cst(), buffer_id)
-		}
-
-		unsafe {
-			gl::BufferData(
-				kind.cst(),
-				size as gl::types::GLsizeiptr,
-				std::ptr::null(),
-				update.cst(),
-			)
-		}
-		unsafe {
-			gl::BindBuffer(kind.cst(), 0)
+			dsa::buffer_data(kind.cst(), buffer_id, size, std::ptr::null(), update.cst(access))
 		}
+		check("RawBuffer::from_null")?;
 		Ok(RawBuffer {
 			id: BufferId(buffer_id),
 			kind,
+			update,
+			access,
 		})
 	}
 
-	pub fn view(&self, n_elems : usize, stride : Option<usize>, offset : usize, gpu_type : GPUInfo) -> BufferView {
-		BufferView { 
-			buffer_id: self.id,
+	pub fn view(&self, n_elems : usize, stride : Option<usize>, offset : usize, gpu_type : GPUInfo) -> Result<BufferView, GLError> {
+		BufferView::new(
+			self.id,
+			self.byte_size(),
 			n_elems,
-			stride: stride.unwrap_or(0),
+			stride.unwrap_or(0),
 			offset,
-			data_info: gpu_type,
+			gpu_type,
+		)
+	}
+
+	// size of the data store in bytes, as reported by the driver
+	pub fn byte_size(&self) -> usize {
+		dsa::buffer_size(self.kind.cst(), self.id.0)
+	}
+
+	// reads "range" (in bytes) back to the CPU ; stalls until the GPU is done writing to the buffer
+	#[track_caller]
+	pub fn read_bytes(&self, range : Range<usize>) -> Result<Vec<u8>, GLError> {
+		if range.start > range.end || range.end > self.byte_size() {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let mut bytes : Vec<u8> = vec![0; range.len()];
+		unsafe {
+			dsa::get_buffer_sub_data(self.id.0, range.start, range.len(), bytes.as_mut_ptr().cast());
+		}
+		check("RawBuffer::read_bytes")?;
+		Ok(bytes)
+	}
+
+	// GPU-side copy of "src_range" (in bytes) of "src" at byte "dst_offset" of this buffer
+	// "src" may be this very buffer as long as both ranges don't overlap
+	#[track_caller]
+	pub fn copy_from(&self, src : &RawBuffer, src_range : Range<usize>, dst_offset : usize) -> Result<(), GLError> {
+		if src_range.start > src_range.end || src_range.end > src.byte_size() {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let dst_end = dst_offset.checked_add(src_range.len()).ok_or(GLError::BufferRangeOutOfBounds)?;
+		if dst_end > self.byte_size() {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		if src.id.0 == self.id.0 && src_range.start < dst_end && dst_offset < src_range.end {
+			return Err(GLError::OverlappingCopy);
+		}
+
+		unsafe {
+			dsa::copy_buffer_sub_data(src.id.0, self.id.0, src_range.start, dst_offset, src_range.len());
 		}
+		check("RawBuffer::copy_from")
+	}
+
+	// desktop GL lets a buffer object be bound to any target, whatever it was created for
+	// e.g. data captured through transform feedback can then be used as vertex data
+	pub fn retarget(self, kind : BufferKind) -> Self
+	{
+		Self { kind, ..self }
 	}
 
 	pub fn as_typed<A>(self, n_elems : usize) -> Buffer<A>
 	{
-		Buffer { raw: self, n_elems, _phantom: std::marker::PhantomData }
+		let capacity = self.byte_size() / std::mem::size_of::<A>().max(1);
+		Buffer { raw: self, n_elems, capacity, _phantom: std::marker::PhantomData }
 	}
 	pub fn as_any_typed(self, n_elems : usize, gpu_type : GPUInfo) -> AnyBuffer
 	{
@@ -242,20 +382,45 @@ impl AnyBuffer {
 	pub fn id(&self)   -> BufferId     { self.raw.id      }
 	#[inline]
 	pub fn kind(&self) -> BufferKind   { self.raw.kind    }
+
+	// "range" is in elements, the bytes are returned as laid out on the GPU
+	#[track_caller]
+	pub fn read_bytes(&self, range : Range<usize>) -> Result<Vec<u8>, GLError> {
+		if range.end > self.n_elems {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let size = self.gpu_info.size();
+		self.raw.read_bytes(range.start * size .. range.end * size)
+	}
+
+	// ranges and offsets in elements ; both buffers must hold the same type of data
+	#[track_caller]
+	pub fn copy_from(&self, src : &AnyBuffer, src_range : Range<usize>, dst_offset : usize) -> Result<(), GLError> {
+		if src.gpu_info != self.gpu_info {
+			return Err(GLError::MismatchedBufferTypes);
+		}
+		if src_range.end > src.n_elems {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let size = self.gpu_info.size();
+		self.raw.copy_from(&src.raw, src_range.start * size .. src_range.end * size, dst_offset * size)
+	}
 }
 
 
 // INVARIANT: whatever data was passed to the GPU, it should be of size "sizeof(A) * n_elems"
+// INVARIANT: n_elems <= capacity, the size of the allocation in elements
 #[derive(Debug, Clone)]
 pub struct Buffer<A> {
 	raw : RawBuffer,
-	pub n_elems : usize,
+	n_elems     : usize,
+	capacity    : usize,
 	_phantom    : std::marker::PhantomData<A>
 }
 
 impl<A : GPUData> Buffer<A> {
 	pub fn to_untyped(self) -> AnyBuffer {
-		let Buffer { raw: buffer, _phantom, n_elems } = self;
+		let Buffer { raw: buffer, n_elems, .. } = self;
 		AnyBuffer { 
 			gpu_info: A::INFO, 
 			n_elems,
@@ -270,78 +435,275 @@ impl<A : GPUData> Buffer<A> {
 
 
 impl<A> Buffer<A> {
+	#[inline]
+	pub fn id(&self)   -> BufferId     { self.raw.id      }
+	#[inline]
+	pub fn kind(&self) -> BufferKind   { self.raw.kind    }
+	#[inline]
+	pub fn len(&self)      -> usize    { self.n_elems     }
+	#[inline]
+	pub fn is_empty(&self) -> bool     { self.n_elems == 0 }
+	#[inline]
+	pub fn capacity(&self) -> usize    { self.capacity    }
+
+	#[inline]
+	pub fn byte_size(&self) -> usize { self.raw.byte_size() }
+
+	// see "RawBuffer::retarget"
+	pub fn retarget(self, kind : BufferKind) -> Self {
+		Self { raw : self.raw.retarget(kind), ..self }
+	}
+
+	// the caller vouches that the first "n_elems" elements were written by the GPU
+	pub(crate) fn set_len(&mut self, n_elems : usize) {
+		self.n_elems = n_elems.min(self.capacity);
+	}
+
 	pub fn interpret_as<B>(self, n_elems : usize) -> Result<Buffer<B>, GLError>{
-		let Buffer { raw: buffer, _phantom, n_elems : n_elems_original } = self;
+		let Buffer { raw: buffer, n_elems : n_elems_original, capacity, .. } = self;
 		if n_elems_original * std::mem::size_of::<A>() < n_elems * std::mem::size_of::<B>()
 		{ return Err(GLError::BufferTooSmallForConversion); }
 		Ok(Buffer::<B> {
 			raw: buffer,
 			n_elems,
+			capacity : capacity * std::mem::size_of::<A>() / std::mem::size_of::<B>().max(1),
 			_phantom: std::marker::PhantomData,
 		})
 	}
 
 
 
+	// "offset" is in elements ; the data must fit in the allocation
+	// elements written past "n_elems" become part of the buffer
+	#[track_caller]
 	pub fn replace_data(
-		&self,
+		&mut self,
 		offset  : usize,
 		data    : &[A],
-	) 
+	) -> Result<(), GLError>
 	{
-
-		unsafe {
-			gl::BindBuffer(self.raw.kind.cst(), self.raw.id.0)
+		let end = offset.checked_add(data.len()).ok_or(GLError::BufferOverflow)?;
+		if end > self.capacity {
+			return Err(GLError::BufferOverflow);
 		}
 
 		unsafe {
-			gl::BufferSubData(
+			dsa::buffer_sub_data(
 				self.raw.kind.cst(),
-				offset as gl::types::GLintptr,
-				std::mem::size_of_val(data) as gl::types::GLsizeiptr,
+				self.raw.id.0,
+				offset * std::mem::size_of::<A>(),
+				std::mem::size_of_val(data),
 				data.as_ptr().cast(),
 			)
 		}
-		unsafe {
-			gl::BindBuffer(self.raw.kind.cst(), 0)
-		}
+		check("Buffer::replace_data")?;
+		self.n_elems = self.n_elems.max(end);
+		Ok(())
+	}
+
+
+
+	// waits until the GPU is done with the commands preceding "fence" (typically the draws reading this buffer)
+	// then replaces the data ; use one fence per buffer when cycling through several buffers
+	#[track_caller]
+	pub fn replace_data_after(
+		&mut self,
+		fence   : &Fence,
+		timeout : std::time::Duration,
+		offset  : usize,
+		data    : &[A],
+	) -> Result<(), GLError>
+	{
+		fence.wait_signaled(timeout)?;
+		self.replace_data(offset, data)
+	}
+
+
+
+	// "range" is in elements, within "n_elems" ; "access" must allow reading, e.g. "MapAccess::READ"
+	// the mapped bytes are read as "A", hence "Pod"
+	#[track_caller]
+	pub fn map_range(&mut self, range : Range<usize>, access : MapAccess) -> Result<MappedSlice<'_, A>, GLError>
+	where A : bytemuck::Pod
+	{
+		MappedSlice::new(self, range, access)
+	}
+
+	// "range" is in elements and may extend past "n_elems", up to the capacity: "n_elems" then grows to its end
+	// "access" must allow writing, e.g. "MapAccess::WRITE.invalidate()"
+	#[track_caller]
+	pub fn map_range_mut(&mut self, range : Range<usize>, access : MapAccess) -> Result<MappedSliceMut<'_, A>, GLError>
+	where A : bytemuck::Pod
+	{
+		MappedSliceMut::new(self, range, access)
 	}
 
 
 
+	// fails if the data doesn't fit in the allocation, use "extend" to grow the buffer
+	#[track_caller]
 	pub fn pass_data(
 		&mut self,
 		data : &[A],
-	) 
+	) -> Result<(), GLError>
 	{
-		self.replace_data(0, data);
+		if data.len() > self.capacity {
+			return Err(GLError::BufferOverflow);
+		}
+		self.replace_data(0, data)?;
 		self.n_elems = data.len();
+		Ok(())
 	}
 
 
 
-	pub fn view<'a, B : GPUData, T>(&'a self, get_field : T) -> BufferView
-	where T : FnOnce(*const A) -> *const B
+	// stalls until the GPU is done writing to the buffer
+	// any bytes must make a valid "A", hence "Pod"
+	#[track_caller]
+	pub fn read(&self, range : Range<usize>) -> Result<Vec<A>, GLError>
+	where A : bytemuck::Pod
 	{
-		let uninit = std::mem::MaybeUninit::<A>::uninit();
-		let base_ptr  = uninit.as_ptr();
-		
-		let field_ptr = get_field(base_ptr);
-		let offset = (field_ptr as usize) - (base_ptr as usize);
+		if range.start > range.end || range.end > self.n_elems {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let mut data : Vec<A> = vec![A::zeroed(); range.len()];
+		unsafe {
+			dsa::get_buffer_sub_data(
+				self.raw.id.0,
+				range.start * std::mem::size_of::<A>(),
+				std::mem::size_of_val(data.as_slice()),
+				data.as_mut_ptr().cast(),
+			);
+		}
+		check("Buffer::read")?;
+		Ok(data)
+	}
 
-		let data_info = B::INFO;
+	// GPU-side copy, "src_range" and "dst_offset" are in elements
+	// the copy must fit in the allocation ; elements written past "n_elems" become part of the buffer
+	#[track_caller]
+	pub fn copy_from(&mut self, src : &Buffer<A>, src_range : Range<usize>, dst_offset : usize) -> Result<(), GLError> {
+		if src_range.start > src_range.end || src_range.end > src.n_elems {
+			return Err(GLError::BufferRangeOutOfBounds);
+		}
+		let dst_end = dst_offset.checked_add(src_range.len()).ok_or(GLError::BufferOverflow)?;
+		if dst_end > self.capacity {
+			return Err(GLError::BufferOverflow);
+		}
+		let elem_size = std::mem::size_of::<A>();
+		self.raw.copy_from(
+			&src.raw,
+			src_range.start * elem_size .. src_range.end * elem_size,
+			dst_offset * elem_size,
+		)?;
+		self.n_elems = self.n_elems.max(dst_end);
+		Ok(())
+	}
 
 
-		BufferView {
-			buffer_id  : self.raw.id,
-			n_elems    : self.n_elems,
-			stride     : std::mem::size_of::<A>(),
-			data_info, 
-			offset,
+
+	// -- Vec-like API
+	// growing past the capacity reallocates the storage of the GL buffer, which keeps its name:
+	// clones, views and bindings of the buffer stay valid
+
+	#[track_caller]
+	pub fn push(&mut self, value : A) -> Result<(), GLError> {
+		self.extend(std::slice::from_ref(&value))
+	}
+
+	#[track_caller]
+	pub fn extend(&mut self, data : &[A]) -> Result<(), GLError> {
+		self.reserve(data.len())?;
+		self.replace_data(self.n_elems, data)
+	}
+
+	// new elements, if any, are copies of "value"
+	#[track_caller]
+	pub fn resize(&mut self, n_elems : usize, value : A) -> Result<(), GLError>
+	where A : Clone
+	{
+		if n_elems <= self.n_elems {
+			self.truncate(n_elems);
+			Ok(())
+		}
+		else {
+			let fill = vec![value; n_elems - self.n_elems];
+			self.extend(&fill)
+		}
+	}
+
+	pub fn truncate(&mut self, n_elems : usize) {
+		self.n_elems = self.n_elems.min(n_elems);
+	}
+
+	pub fn clear(&mut self) {
+		self.n_elems = 0;
+	}
+
+	#[track_caller]
+	pub fn reserve(&mut self, additional : usize) -> Result<(), GLError> {
+		let needed = self.n_elems.checked_add(additional).ok_or(GLError::BufferOverflow)?;
+		if needed <= self.capacity {
+			return Ok(());
+		}
+		let new_capacity = needed.max(2 * self.capacity);
+		self.reallocate(new_capacity)
+	}
+
+	// glBufferData replaces the content, which is kept in a temporary buffer meanwhile
+	#[track_caller]
+	fn reallocate(&mut self, new_capacity : usize) -> Result<(), GLError> {
+		let elem_size = std::mem::size_of::<A>();
+		let new_size  = new_capacity.checked_mul(elem_size).ok_or(GLError::BufferOverflow)?;
+		let used      = self.n_elems * elem_size;
+		let RawBuffer { id, kind, update, access } = self.raw;
+
+		let resize = || unsafe {
+			dsa::buffer_data(kind.cst(), id.0, new_size, std::ptr::null(), update.cst(access));
+		};
+		if used == 0 {
+			resize();
+		}
+		else {
+			let temporary = RawBuffer::from_null(UpdateKind::Stream, AccessKind::Copy, BufferKind::CopyWrite, used)?;
+			let copied = temporary
+				.copy_from(&self.raw, 0 .. used, 0)
+				.and_then(|()| {
+					resize();
+					self.raw.copy_from(&temporary, 0 .. used, 0)
+				});
+			unsafe {gl::DeleteBuffers(1, &temporary.id.0);}
+			copied?;
 		}
+		check("Buffer::reallocate")?;
+
+		self.capacity = new_capacity;
+		Ok(())
 	}
 
 
+
+	// the field of every element, e.g. "buffer.view(field!(Vertex, normal))"
+	pub fn view<B : GPUData>(&self, field : Field<A, B>) -> Result<BufferView, GLError>
+	{
+		let elem_size = std::mem::size_of::<A>();
+		let field_end = field.offset.checked_add(std::mem::size_of::<B>());
+		if field_end.is_none_or(|end| end > elem_size) {
+			return Err(GLError::BufferViewOutOfRange);
+		}
+
+		BufferView::new(
+			self.raw.id,
+			self.capacity * elem_size,
+			self.n_elems,
+			elem_size,
+			field.offset,
+			B::INFO,
+		)
+	}
+
+
+	#[track_caller]
 	pub fn register(self, gl : &mut GLWrap) -> Result<UniformBuffer<A>, GLError>  {
 		if self.raw.kind != BufferKind::UniformBuffer  {
 			Err(GLError::IsntUniformBuffer)
@@ -358,6 +720,7 @@ impl<A> Buffer<A> {
 				); 
 				gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
 			}
+			check("Buffer::register")?;
 
 			Ok(UniformBuffer {
 				buffer : self,
@@ -368,79 +731,125 @@ impl<A> Buffer<A> {
 }
 
 impl<A : GPUData> Buffer<A> {
-	pub fn direct_view(&self) -> BufferView
+	pub fn direct_view(&self) -> Result<BufferView, GLError>
 	{
 		self.view_range(0 .. self.n_elems)
 	}
 
-	pub fn view_range(&self, range : Range<usize>) -> BufferView
+	// "range" is in elements, and may extend past "n_elems" up to the capacity
+	pub fn view_range(&self, range : Range<usize>) -> Result<BufferView, GLError>
 	{
+		if range.start > range.end {
+			return Err(GLError::BufferViewOutOfRange);
+		}
 		let stride = std::mem::size_of::<A>();
-		BufferView {
-			buffer_id  : self.raw.id,
-			n_elems    : range.len(),
+		let offset = range.start.checked_mul(stride).ok_or(GLError::BufferViewOutOfRange)?;
+		BufferView::new(
+			self.raw.id,
+			self.capacity * stride,
+			range.len(),
 			stride,
-			data_info  : A::INFO, 
-			offset     : range.start * stride,
-		}
+			offset,
+			A::INFO,
+		)
 	}
 }
 
+
+// a field of type "B" in elements of type "A", built by "field!"
+#[derive(Debug)]
+pub struct Field<A, B> {
+	offset   : usize,
+	_phantom : std::marker::PhantomData<fn(&A) -> &B>,
+}
+
+impl<A, B> Field<A, B> {
+	// "get" only gives the type of the field, it is never called
+	#[doc(hidden)]
+	pub fn new(offset : usize, _get : fn(&A) -> &B) -> Self {
+		Self { offset, _phantom : std::marker::PhantomData }
+	}
+
+	#[inline]
+	pub fn offset(&self) -> usize { self.offset }
+}
+
+// "field!(Vertex, normal)" : the "normal" field of a "Vertex"
 #[macro_export]
 macro_rules! field {
-	($field : tt) => {(|buffer_ref| unsafe{ &((*buffer_ref).$field) as *const _})}
+	($type : ty, $field : tt) => {
+		$crate::buffer::Field::<$type, _>::new(
+			::std::mem::offset_of!($type, $field),
+			|element : &$type| &element.$field,
+		)
+	};
 }
 
-#[derive(Clone)]
+// INVARIANT (checked by "BufferView::new" & "Program::bind"): every element read lies within the buffer
+#[derive(Debug, Clone)]
 pub struct BufferView {
-	pub buffer_id: BufferId,
-	pub n_elems:   usize,
-	pub stride:    usize,
-	pub offset:    usize,
-	pub data_info: GPUInfo,
+	buffer_id:  BufferId,
+	buffer_len: usize,
+	n_elems:    usize,
+	stride:     usize,
+	offset:     usize,
+	data_info:  GPUInfo,
 }
 
 impl BufferView {
-    pub fn new(
-    	buffer_id: BufferId, 
-    	n_elems:   usize, 
-    	stride:    usize, 
-    	offset:    usize, 
-    	data_info: GPUInfo
-    ) -> Self { Self { buffer_id, stride, offset, data_info, n_elems } }
-
-	pub fn bind_to(self, pos : AttributePos) {
-		let BufferView {
-			buffer_id, stride, offset,
-			data_info : GPUInfo {n_components, gl_type}, ..
-		} = self;
-		unsafe {gl::BindBuffer(gl::ARRAY_BUFFER, buffer_id.0);}
-
-		if gl_type.is_integer() {
-			unsafe {
-				gl::VertexAttribIPointer(
-					pos.0,
-					n_components as gl::types::GLint,
-					gl_type.to_opengl_sym(),
-					stride as gl::types::GLsizei,
-					offset as *const _,
-				)
-			}
+	// "buffer_len" is the size of the buffer in bytes, a "stride" of 0 means tightly packed elements
+	pub fn new(
+		buffer_id:  BufferId, 
+		buffer_len: usize,
+		n_elems:    usize, 
+		stride:     usize, 
+		offset:     usize, 
+		data_info:  GPUInfo
+	) -> Result<Self, GLError> {
+		let view = Self::new_unchecked(buffer_id, buffer_len, n_elems, stride, offset, data_info);
+		view.validate()?;
+		Ok(view)
+	}
+
+	pub(crate) fn new_unchecked(
+		buffer_id:  BufferId, 
+		buffer_len: usize,
+		n_elems:    usize, 
+		stride:     usize, 
+		offset:     usize, 
+		data_info:  GPUInfo
+	) -> Self { Self { buffer_id, buffer_len, stride, offset, data_info, n_elems } }
+
+	#[inline]
+	pub fn buffer_id(&self)  -> BufferId { self.buffer_id  }
+	#[inline]
+	pub fn buffer_len(&self) -> usize    { self.buffer_len }
+	#[inline]
+	pub fn n_elems(&self)    -> usize    { self.n_elems    }
+	#[inline]
+	pub fn stride(&self)     -> usize    { self.stride     }
+	#[inline]
+	pub fn offset(&self)     -> usize    { self.offset     }
+	#[inline]
+	pub fn data_info(&self)  -> &GPUInfo { &self.data_info }
+
+	// checks that "offset + stride * (n_elems - 1) + size of element <= buffer_len"
+	pub fn validate(&self) -> Result<(), GLError> {
+		if self.n_elems == 0 {
+			return Ok(());
 		}
-		else {
-			unsafe {
-				gl::VertexAttribPointer(
-					pos.0,
-					n_components as gl::types::GLint,
-					gl_type.to_opengl_sym(),
-					gl::FALSE,
-					stride as gl::types::GLsizei,
-					offset as *const _,
-				)
-			}
+		let elem_size = self.data_info.size();
+		let stride = if self.stride == 0 { elem_size } else { self.stride };
+		let end = stride
+			.checked_mul(self.n_elems - 1)
+			.and_then(|x| x.checked_add(self.offset))
+			.and_then(|x| x.checked_add(elem_size));
+		match end {
+			Some(end) if end <= self.buffer_len => Ok(()),
+			_                                   => Err(GLError::BufferViewOutOfRange),
 		}
-		unsafe {gl::BindBuffer(gl::ARRAY_BUFFER, 0);}
 	}
+
 }
 
 
@@ -457,3 +866,105 @@ pub trait BindingPoint {
 
 
 
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::math3d::V3;
+	use crate::trace::{self, Value};
+
+	fn view(buffer_len : usize, n_elems : usize, stride : usize, offset : usize) -> BufferView {
+		BufferView::new_unchecked(BufferId(0), buffer_len, n_elems, stride, offset, V3::INFO)
+	}
+
+	#[test]
+	fn tightly_packed_view_fits() {
+		assert!(view(36, 3, 0,  0).validate().is_ok());
+		assert!(view(36, 3, 12, 0).validate().is_ok());
+		assert!(view(36, 4, 0,  0).validate().is_err());
+		assert!(view(36, 3, 0,  4).validate().is_err());
+	}
+
+	#[test]
+	fn interleaved_view_fits() {
+		// two V3 per element, viewing the second one
+		assert!(view(72, 3, 24, 12).validate().is_ok());
+		assert!(view(71, 3, 24, 12).validate().is_err());
+	}
+
+	#[test]
+	fn empty_view_always_fits() {
+		assert!(view(0, 0, 12, 1000).validate().is_ok());
+	}
+
+	#[test]
+	fn overflowing_view_is_rejected() {
+		assert!(view(36, usize::MAX, 12, 0).validate().is_err());
+	}
+
+	#[test]
+	fn growing_keeps_the_buffer_name() {
+		let recorder = trace::Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let mut buffer = BufferBld::array().data(&[1u32, 2, 3]).unwrap();
+		let id = buffer.id().0;
+		trace.clear();
+		buffer.push(4).unwrap();
+		assert_eq!(buffer.id().0, id);
+		assert_eq!((buffer.len(), buffer.capacity()), (4, 6));
+		// only the temporary buffer
+		assert_eq!(trace.named("DeleteBuffers").len(), 1);
+
+		// the 12 bytes in use go to a temporary buffer and back, around the new data store of 24 bytes
+		let copies = trace.named("CopyBufferSubData");
+		assert_eq!(copies.len(), 2);
+		assert!(copies.iter().all(|call| call.args[4] == Value::of(12isize)));
+		let sizes = trace.named("BufferData").iter().map(|call| call.args[1]).collect::<Vec<_>>();
+		assert!(sizes.contains(&Value::of(24isize)));
+	}
+
+	#[test]
+	fn read_fetches_the_range_in_bytes() {
+		let recorder = trace::Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let buffer = BufferBld::array().data(&[V3::new([1., 2., 3.]); 4]).unwrap();
+		assert_eq!(buffer.read(1 .. 3).unwrap().len(), 2);
+		let fetch = &trace.named("GetBufferSubData")[0];
+		assert_eq!(fetch.args[1 .. 3], [Value::of(12isize), Value::of(24isize)]);
+		assert!(matches!(buffer.read(2 .. 5), Err(GLError::BufferRangeOutOfBounds)));
+	}
+
+	#[test]
+	fn field_views_are_validated_at_construction() {
+		let recorder = trace::Recorder::new();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		#[repr(C)]
+		struct Vertex {
+			position : V3,
+			normal   : V3,
+		}
+		let vertex = || Vertex { position : V3::new([0.; 3]), normal : V3::new([1.; 3]) };
+		let buffer = BufferBld::array().data(&[vertex(), vertex()]).unwrap();
+
+		let normals = buffer.view(field!(Vertex, normal)).unwrap();
+		assert_eq!((normals.offset(), normals.stride(), normals.n_elems()), (12, 24, 2));
+		assert!(matches!(buffer.view(Field::<Vertex, V3>::new(16, |vertex| &vertex.normal)), Err(GLError::BufferViewOutOfRange)));
+
+		let positions = BufferBld::array().data(&[V3::new([0.; 3]); 4]).unwrap();
+		assert!(positions.view_range(1 .. 4).is_ok());
+		assert!(matches!(positions.view_range(2 .. 5), Err(GLError::BufferViewOutOfRange)));
+		#[allow(clippy::reversed_empty_ranges)]
+		let reversed = positions.view_range(3 .. 1);
+		assert!(matches!(reversed, Err(GLError::BufferViewOutOfRange)));
+	}
+}
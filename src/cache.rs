@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::capabilities::{self, Capabilities};
+use crate::error::*;
+use crate::preprocess::*;
+use crate::pipeline::*;
+use crate::program::*;
+use crate::shader::*;
+
+
+
+// -- PROGRAM CACHE
+// stores linked programs on disk with glGetProgramBinary, skipping compilation & linking on later runs
+// entries are keyed by the preprocessed sources and the driver (vendor, renderer, version):
+// a driver update simply misses the cache, and a binary rejected by the driver is rebuilt from source
+//
+// file layout, integers in little endian:
+//   magic, driver string, binary format (u32), stages (u32), separable (u8),
+//   number of attributes (u32), each attribute as length (u32) + bytes, binary
+
+const MAGIC : &[u8; 8] = b"MIGLPB02";
+
+#[derive(Debug)]
+pub struct ProgramCache {
+	directory : PathBuf,
+	driver    : String,
+}
+
+impl ProgramCache {
+	// needs a current context, to identify the driver
+	pub fn new(directory : &str) -> Result<Self, GLError> {
+		std::fs::create_dir_all(directory)?;
+		let capabilities = capabilities::current().unwrap_or_else(|| Arc::new(Capabilities::query()));
+		let driver = [&capabilities.vendor, &capabilities.renderer, &capabilities.version]
+			.map(String::as_str)
+			.join("\n");
+		Ok(Self { directory : PathBuf::from(directory), driver })
+	}
+
+	// "configure" only runs on a cache miss: programs with the same sources must be configured the same way
+	pub fn build<F>(
+		&self,
+		vert      : &Preprocessed,
+		frag      : &Preprocessed,
+		geom      : Option<&Preprocessed>,
+		configure : F,
+	) -> Result<Program, GLError>
+	where F : FnOnce(ProgramBuilder) -> ProgramBuilder
+	{
+		let path = self.entry_path(vert, frag, geom);
+		if let Some(program) = self.load(&path) {
+			return Ok(program);
+		}
+
+		let mut builder = ProgramBuilder::new(
+			Shader::<Vertex>::from_preprocessed(vert)?,
+			Shader::<Fragment>::from_preprocessed(frag)?,
+		);
+		if let Some(geom) = geom {
+			builder = builder.geom_shader(Shader::<Geometry>::from_preprocessed(geom)?);
+		}
+		let program = configure(builder).retrievable_binary().build()?;
+
+		// a cache that can't be written only costs time on the next run
+		let _ = self.store(&path, &program);
+		Ok(program)
+	}
+
+	// removes every entry
+	pub fn clear(&self) -> Result<(), GLError> {
+		for entry in std::fs::read_dir(&self.directory)? {
+			let path = entry?.path();
+			if path.extension().is_some_and(|extension| extension == "bin") {
+				std::fs::remove_file(path)?;
+			}
+		}
+		Ok(())
+	}
+
+
+	fn entry_path(&self, vert : &Preprocessed, frag : &Preprocessed, geom : Option<&Preprocessed>) -> PathBuf {
+		let mut hash = Fnv1a::new();
+		hash.write(self.driver.as_bytes());
+		for source in [Some(vert), Some(frag), geom] {
+			// the separator keeps a missing stage apart from an empty one
+			match source {
+				Some(source) => { hash.write(&[1]); hash.write(source.source.as_bytes()); },
+				None         => hash.write(&[0]),
+			}
+		}
+		self.directory.join(format!("{:016x}.bin", hash.finish()))
+	}
+
+	// None on a miss, a corrupted entry or a binary the driver rejects
+	fn load(&self, path : &Path) -> Option<Program> {
+		let bytes = std::fs::read(path).ok()?;
+		let mut reader = Reader(&bytes);
+
+		if reader.take(MAGIC.len())? != MAGIC {
+			return None;
+		}
+		if reader.string()? != self.driver {
+			return None;
+		}
+		let format       = reader.u32()?;
+		let stages       = PipelineStages(reader.u32()?);
+		let separable    = reader.take(1)?[0] != 0;
+		let n_attributes = reader.u32()?;
+		let attributes   = (0 .. n_attributes)
+			.map(|_| reader.string())
+			.collect::<Option<Vec<String>>>()?;
+
+		Program::from_binary(format, reader.0, stages, separable, Some(attributes)).ok()
+	}
+
+	fn store(&self, path : &Path, program : &Program) -> Result<(), GLError> {
+		let (format, binary) = match program.binary() {
+			Some(binary) => binary,
+			None         => return Ok(()),
+		};
+		let attributes = program.attribute_names();
+
+		let mut bytes = Vec::with_capacity(binary.len() + 256);
+		bytes.extend_from_slice(MAGIC);
+		write_string(&mut bytes, &self.driver);
+		bytes.extend_from_slice(&format.to_le_bytes());
+		bytes.extend_from_slice(&program.stages().0.to_le_bytes());
+		bytes.push(program.is_separable() as u8);
+		bytes.extend_from_slice(&(attributes.len() as u32).to_le_bytes());
+		for attribute in &attributes {
+			write_string(&mut bytes, attribute);
+		}
+		bytes.extend_from_slice(&binary);
+
+		// written aside then renamed, so that a concurrent run never reads a partial entry
+		let temporary = path.with_extension("tmp");
+		std::fs::write(&temporary, bytes)?;
+		std::fs::rename(temporary, path)?;
+		Ok(())
+	}
+}
+
+
+fn write_string(bytes : &mut Vec<u8>, string : &str) {
+	bytes.extend_from_slice(&(string.len() as u32).to_le_bytes());
+	bytes.extend_from_slice(string.as_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, n : usize) -> Option<&'a [u8]> {
+		if self.0.len() < n {
+			return None;
+		}
+		let (taken, rest) = self.0.split_at(n);
+		self.0 = rest;
+		Some(taken)
+	}
+
+	fn u32(&mut self) -> Option<u32> {
+		Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+	}
+
+	fn string(&mut self) -> Option<String> {
+		let length = self.u32()? as usize;
+		String::from_utf8(self.take(length)?.to_vec()).ok()
+	}
+}
+
+
+// unlike std's hasher, stable across compiler versions
+struct Fnv1a(u64);
+
+impl Fnv1a {
+	fn new() -> Self { Self(0xcbf2_9ce4_8422_2325) }
+
+	fn write(&mut self, bytes : &[u8]) {
+		for byte in bytes {
+			self.0 ^= *byte as u64;
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+
+	fn finish(&self) -> u64 { self.0 }
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fnv1a_reference_values() {
+		let hash = |bytes : &[u8]| { let mut hash = Fnv1a::new(); hash.write(bytes); hash.finish() };
+		assert_eq!(hash(b""),  0xcbf2_9ce4_8422_2325);
+		assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+	}
+
+	#[test]
+	fn reader_stops_at_truncated_data() {
+		let mut bytes = Vec::new();
+		write_string(&mut bytes, "position");
+		bytes.extend_from_slice(&7u32.to_le_bytes());
+
+		let mut reader = Reader(&bytes);
+		assert_eq!(reader.string().as_deref(), Some("position"));
+		assert_eq!(reader.u32(), Some(7));
+		assert_eq!(reader.u32(), None);
+
+		let mut truncated = Reader(&bytes[.. 6]);
+		assert_eq!(truncated.string(), None);
+	}
+}
@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::sync::Arc;
+
+use gl::types::*;
+
+use crate::error::*;
+
+
+
+// -- CAPABILITIES
+// what the context provides, queried once by "GLWrap" and shared through "current"
+// APIs relying on a feature past GL 3.3 check it with "require" and fail with "GLError::Unsupported"
+// rather than calling an entry point the driver lacks
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Feature {
+	DirectStateAccess,
+	DrawIndirect,
+	MultiDrawIndirect,
+	BufferStorage,
+	SeparatePrograms,
+	ProgramBinary,
+	TimerQueries,
+	DebugOutput,
+	Instancing,
+	Compute,
+}
+
+impl Feature {
+	// the core version the feature appeared in, and the extension providing it before
+	fn core_version_and_extension(self) -> ((u32, u32), &'static str) {
+		match self {
+			Self::DirectStateAccess => ((4, 5), "GL_ARB_direct_state_access"),
+			Self::DrawIndirect      => ((4, 0), "GL_ARB_draw_indirect"),
+			Self::MultiDrawIndirect => ((4, 3), "GL_ARB_multi_draw_indirect"),
+			Self::BufferStorage     => ((4, 4), "GL_ARB_buffer_storage"),
+			Self::SeparatePrograms  => ((4, 1), "GL_ARB_separate_shader_objects"),
+			Self::ProgramBinary     => ((4, 1), "GL_ARB_get_program_binary"),
+			Self::TimerQueries      => ((3, 3), "GL_ARB_timer_query"),
+			Self::DebugOutput       => ((4, 3), "GL_KHR_debug"),
+			Self::Instancing        => ((3, 3), "GL_ARB_instanced_arrays"),
+			Self::Compute           => ((4, 3), "GL_ARB_compute_shader"),
+		}
+	}
+}
+
+
+impl std::fmt::Display for Feature {
+	fn fmt(&self, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let ((major, minor), extension) = self.core_version_and_extension();
+		write!(f, "{:?} (GL {}.{} or {})", self, major, minor, extension)
+	}
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+	pub vendor       : String,
+	pub renderer     : String,
+	pub version      : String,
+	pub glsl_version : String,
+	// parsed from "version", e.g. (4, 6)
+	pub major        : u32,
+	pub minor        : u32,
+	pub extensions   : HashSet<String>,
+
+	pub max_texture_size           : usize,
+	pub max_vertex_attribs         : usize,
+	pub max_uniform_block_size     : usize,
+	pub max_draw_buffers           : usize,
+	pub max_samples                : usize,
+	pub max_combined_texture_units : usize,
+}
+
+impl Capabilities {
+	// needs a current context
+	pub fn query() -> Self {
+		let (mut major, mut minor) = (0, 0);
+		let mut n_extensions = 0;
+		unsafe {
+			gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+			gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+			gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut n_extensions);
+		}
+		let extensions = (0 .. n_extensions.max(0) as GLuint)
+			.filter_map(|i| {
+				let extension = unsafe {gl::GetStringi(gl::EXTENSIONS, i)};
+				if extension.is_null() {
+					return None;
+				}
+				Some(unsafe {CStr::from_ptr(extension.cast())}.to_string_lossy().into_owned())
+			})
+			.collect();
+
+		let mut max_uniform_block_size : GLint64 = 0;
+		unsafe {gl::GetInteger64v(gl::MAX_UNIFORM_BLOCK_SIZE, &mut max_uniform_block_size)};
+
+		Self {
+			vendor       : gl_string(gl::VENDOR),
+			renderer     : gl_string(gl::RENDERER),
+			version      : gl_string(gl::VERSION),
+			glsl_version : gl_string(gl::SHADING_LANGUAGE_VERSION),
+			major        : major.max(0) as u32,
+			minor        : minor.max(0) as u32,
+			extensions,
+
+			max_texture_size           : get_integer(gl::MAX_TEXTURE_SIZE),
+			max_vertex_attribs         : get_integer(gl::MAX_VERTEX_ATTRIBS),
+			max_uniform_block_size     : max_uniform_block_size.max(0) as usize,
+			max_draw_buffers           : get_integer(gl::MAX_DRAW_BUFFERS),
+			max_samples                : get_integer(gl::MAX_SAMPLES),
+			max_combined_texture_units : get_integer(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS),
+		}
+	}
+
+	pub fn at_least(&self, major : u32, minor : u32) -> bool {
+		(self.major, self.minor) >= (major, minor)
+	}
+
+	// e.g. "GL_ARB_bindless_texture"
+	pub fn has_extension(&self, name : &str) -> bool {
+		self.extensions.contains(name)
+	}
+
+	// GLSL version as used in a "#version" directive, e.g. 460 for "4.60 NVIDIA"
+	pub fn glsl_version_number(&self) -> Option<u32> {
+		let number = self.glsl_version.split_whitespace().next()?;
+		let (major, minor) = number.split_once('.')?;
+		let minor = minor.get(.. 2).unwrap_or(minor);
+		Some(major.parse::<u32>().ok()? * 100 + minor.parse::<u32>().ok()?)
+	}
+
+	pub fn supports(&self, feature : Feature) -> bool {
+		let ((major, minor), extension) = feature.core_version_and_extension();
+		self.at_least(major, minor) || self.has_extension(extension)
+	}
+
+	pub fn require(&self, feature : Feature) -> Result<(), GLError> {
+		if self.supports(feature) { Ok(()) } else { Err(GLError::Unsupported(feature)) }
+	}
+}
+
+
+
+// -- CURRENT CONTEXT
+// per thread, like GL contexts
+
+thread_local! {
+	static CURRENT : RefCell<Option<Arc<Capabilities>>> = const { RefCell::new(None) };
+}
+
+// those of the last "GLWrap" created on this thread, None before
+pub fn current() -> Option<Arc<Capabilities>> {
+	CURRENT.with_borrow(|current| current.clone())
+}
+
+pub(crate) fn set_current(capabilities : Arc<Capabilities>) {
+	CURRENT.set(Some(capabilities));
+}
+
+// without a "GLWrap" nothing is known, and the call is left to the driver
+pub(crate) fn require(feature : Feature) -> Result<(), GLError> {
+	match current() {
+		Some(capabilities) => capabilities.require(feature),
+		None               => Ok(()),
+	}
+}
+
+
+fn gl_string(name : GLenum) -> String {
+	let string = unsafe {gl::GetString(name)};
+	if string.is_null() {
+		return String::new();
+	}
+	unsafe {CStr::from_ptr(string.cast())}.to_string_lossy().into_owned()
+}
+
+fn get_integer(name : GLenum) -> usize {
+	let mut value = 0;
+	unsafe {gl::GetIntegerv(name, &mut value)};
+	value.max(0) as usize
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn capabilities(major : u32, minor : u32, extensions : &[&str], glsl_version : &str) -> Capabilities {
+		Capabilities {
+			vendor       : String::new(),
+			renderer     : String::new(),
+			version      : format!("{}.{}", major, minor),
+			glsl_version : glsl_version.to_string(),
+			major,
+			minor,
+			extensions   : extensions.iter().map(|extension| extension.to_string()).collect(),
+
+			max_texture_size           : 0,
+			max_vertex_attribs         : 0,
+			max_uniform_block_size     : 0,
+			max_draw_buffers           : 0,
+			max_samples                : 0,
+			max_combined_texture_units : 0,
+		}
+	}
+
+	#[test]
+	fn features_come_from_version_or_extension() {
+		let gl_33 = capabilities(3, 3, &["GL_ARB_buffer_storage"], "3.30");
+		assert!(gl_33.supports(Feature::Instancing));
+		assert!(gl_33.supports(Feature::BufferStorage));
+		assert!(!gl_33.supports(Feature::DirectStateAccess));
+		assert!(matches!(gl_33.require(Feature::Compute), Err(GLError::Unsupported(Feature::Compute))));
+
+		let gl_46 = capabilities(4, 6, &[], "4.60");
+		assert!(gl_46.supports(Feature::DirectStateAccess));
+		assert!(gl_46.require(Feature::MultiDrawIndirect).is_ok());
+	}
+
+	#[test]
+	fn glsl_version_number() {
+		assert_eq!(capabilities(4, 6, &[], "4.60 NVIDIA").glsl_version_number(), Some(460));
+		assert_eq!(capabilities(3, 3, &[], "3.30").glsl_version_number(), Some(330));
+		assert_eq!(capabilities(4, 1, &[], "4.10 - Build 1.2").glsl_version_number(), Some(410));
+		assert_eq!(capabilities(3, 3, &[], "").glsl_version_number(), None);
+	}
+}
@@ -0,0 +1,263 @@
+use std::ffi::{c_void, CStr};
+
+use gl::types::*;
+
+use crate::capabilities::{self, Feature};
+
+
+
+// -- DEBUG OUTPUT
+// with GL 4.3 or KHR_debug, the driver reports invalid calls, performance warnings... to a callback
+// messages are delivered synchronously, from the call that triggered them, so a breakpoint in the
+// callback shows the offending call in the stack
+//
+// labels & groups name objects and sections of a frame in tools like apitrace or RenderDoc
+// they are skipped when the feature is unavailable
+
+
+// from least to most severe
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum DebugSeverity {
+	Notification,
+	Low,
+	Medium,
+	High,
+}
+
+impl DebugSeverity {
+	pub fn from_opengl_sym(symbol : GLenum) -> Option<Self> {
+		match symbol {
+			gl::DEBUG_SEVERITY_NOTIFICATION => Some(Self::Notification),
+			gl::DEBUG_SEVERITY_LOW          => Some(Self::Low),
+			gl::DEBUG_SEVERITY_MEDIUM       => Some(Self::Medium),
+			gl::DEBUG_SEVERITY_HIGH         => Some(Self::High),
+			_ => None
+		}
+	}
+
+	pub fn to_opengl_sym(self) -> GLenum {
+		match self {
+			Self::Notification => gl::DEBUG_SEVERITY_NOTIFICATION,
+			Self::Low          => gl::DEBUG_SEVERITY_LOW,
+			Self::Medium       => gl::DEBUG_SEVERITY_MEDIUM,
+			Self::High         => gl::DEBUG_SEVERITY_HIGH,
+		}
+	}
+
+	const ALL : [Self; 4] = [Self::Notification, Self::Low, Self::Medium, Self::High];
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DebugSource {
+	Api,
+	WindowSystem,
+	ShaderCompiler,
+	ThirdParty,
+	Application,
+	Other,
+}
+
+impl DebugSource {
+	fn from_opengl_sym(symbol : GLenum) -> Self {
+		match symbol {
+			gl::DEBUG_SOURCE_API             => Self::Api,
+			gl::DEBUG_SOURCE_WINDOW_SYSTEM   => Self::WindowSystem,
+			gl::DEBUG_SOURCE_SHADER_COMPILER => Self::ShaderCompiler,
+			gl::DEBUG_SOURCE_THIRD_PARTY     => Self::ThirdParty,
+			gl::DEBUG_SOURCE_APPLICATION     => Self::Application,
+			_ => Self::Other,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DebugKind {
+	Error,
+	DeprecatedBehavior,
+	UndefinedBehavior,
+	Portability,
+	Performance,
+	Marker,
+	PushGroup,
+	PopGroup,
+	Other,
+}
+
+impl DebugKind {
+	fn from_opengl_sym(symbol : GLenum) -> Self {
+		match symbol {
+			gl::DEBUG_TYPE_ERROR               => Self::Error,
+			gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => Self::DeprecatedBehavior,
+			gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR  => Self::UndefinedBehavior,
+			gl::DEBUG_TYPE_PORTABILITY         => Self::Portability,
+			gl::DEBUG_TYPE_PERFORMANCE         => Self::Performance,
+			gl::DEBUG_TYPE_MARKER              => Self::Marker,
+			gl::DEBUG_TYPE_PUSH_GROUP          => Self::PushGroup,
+			gl::DEBUG_TYPE_POP_GROUP           => Self::PopGroup,
+			_ => Self::Other,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugMessage {
+	pub source   : DebugSource,
+	pub kind     : DebugKind,
+	pub id       : GLuint,
+	pub severity : DebugSeverity,
+	pub message  : String,
+}
+
+
+type Callback = Box<dyn FnMut(&DebugMessage)>;
+
+// keeps the callback alive while registered, unregisters it when dropped
+pub(crate) struct DebugOutput {
+	_callback : Box<Callback>,
+}
+
+impl DebugOutput {
+	// needs "Feature::DebugOutput"
+	pub(crate) fn enable(min_severity : DebugSeverity, callback : Callback) -> Self {
+		let callback = Box::new(callback);
+		unsafe {
+			gl::Enable(gl::DEBUG_OUTPUT);
+			gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+			for severity in DebugSeverity::ALL {
+				gl::DebugMessageControl(
+					gl::DONT_CARE,
+					gl::DONT_CARE,
+					severity.to_opengl_sym(),
+					0,
+					std::ptr::null(),
+					(severity >= min_severity) as GLboolean,
+				);
+			}
+			let user_param : *const Callback = &*callback;
+			gl::DebugMessageCallback(Some(receive), user_param.cast());
+		}
+		Self { _callback : callback }
+	}
+}
+
+impl Drop for DebugOutput {
+	fn drop(&mut self) {
+		unsafe {
+			gl::DebugMessageCallback(None, std::ptr::null());
+			gl::Disable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+			gl::Disable(gl::DEBUG_OUTPUT);
+		}
+	}
+}
+
+impl std::fmt::Debug for DebugOutput {
+	fn fmt(&self, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("DebugOutput")
+	}
+}
+
+extern "system" fn receive(
+	source     : GLenum,
+	kind       : GLenum,
+	id         : GLuint,
+	severity   : GLenum,
+	length     : GLsizei,
+	message    : *const GLchar,
+	user_param : *mut c_void,
+	)
+{
+	let message = if length < 0 {
+		unsafe {CStr::from_ptr(message)}.to_string_lossy().into_owned()
+	}
+	else {
+		let bytes = unsafe {std::slice::from_raw_parts(message.cast::<u8>(), length as usize)};
+		String::from_utf8_lossy(bytes).into_owned()
+	};
+	let message = DebugMessage {
+		source   : DebugSource::from_opengl_sym(source),
+		kind     : DebugKind::from_opengl_sym(kind),
+		id,
+		severity : DebugSeverity::from_opengl_sym(severity).unwrap_or(DebugSeverity::Notification),
+		message,
+	};
+
+	// unwinding into the driver would abort
+	let callback = unsafe {&mut *user_param.cast::<Callback>()};
+	let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(&message)));
+}
+
+// forwards messages to the "log" crate, at a level matching their severity
+#[cfg(feature = "log")]
+pub(crate) fn log_message(message : &DebugMessage) {
+	let level = match message.severity {
+		DebugSeverity::High         => log::Level::Error,
+		DebugSeverity::Medium       => log::Level::Warn,
+		DebugSeverity::Low          => log::Level::Info,
+		DebugSeverity::Notification => log::Level::Debug,
+	};
+	log::log!(target : "migl", level, "{:?} {:?} ({}): {}", message.source, message.kind, message.id, message.message);
+}
+
+
+
+// -- LABELS & GROUPS
+
+fn is_available() -> bool {
+	capabilities::current().is_some_and(|capabilities| capabilities.supports(Feature::DebugOutput))
+}
+
+// "identifier" is the kind of object, e.g. gl::BUFFER
+pub(crate) fn set_label(identifier : GLenum, name : GLuint, label : &str) {
+	if !is_available() {
+		return;
+	}
+	unsafe {gl::ObjectLabel(identifier, name, label.len() as GLsizei, label.as_ptr().cast())};
+}
+
+pub(crate) fn push_group(name : &str) {
+	if !is_available() {
+		return;
+	}
+	unsafe {gl::PushDebugGroup(gl::DEBUG_SOURCE_APPLICATION, 0, name.len() as GLsizei, name.as_ptr().cast())};
+}
+
+pub(crate) fn pop_group() {
+	if !is_available() {
+		return;
+	}
+	unsafe {gl::PopDebugGroup()};
+}
+
+// pops the group pushed by "GLWrap::debug_group" when dropped
+#[derive(Debug)]
+pub struct DebugGroup(());
+
+impl DebugGroup {
+	pub(crate) fn push(name : &str) -> Self {
+		push_group(name);
+		Self(())
+	}
+}
+
+impl Drop for DebugGroup {
+	fn drop(&mut self) { pop_group(); }
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn severities_are_ordered() {
+		assert!(DebugSeverity::High > DebugSeverity::Medium);
+		assert!(DebugSeverity::Low  > DebugSeverity::Notification);
+		for severity in DebugSeverity::ALL {
+			assert_eq!(DebugSeverity::from_opengl_sym(severity.to_opengl_sym()), Some(severity));
+		}
+	}
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+
+use crate::preprocess::*;
+
+
+
+// -- DIAGNOSTICS
+// compile & link logs parsed into one entry per message
+// recognized formats, where F is the source string number and L the line:
+//   Mesa:          "F:L(C): error: message"
+//   NVIDIA:        "F(L) : error C0000: message"
+//   AMD, Intel...: "ERROR: F:L: message"
+// lines in none of these formats are kept as messages without location
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+	Vertex,
+	Fragment,
+	Geometry,
+	Link,
+}
+
+impl fmt::Display for Stage {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Stage::Vertex   => "vertex shader",
+			Stage::Fragment => "fragment shader",
+			Stage::Geometry => "geometry shader",
+			Stage::Link     => "program link",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+	Note,
+}
+
+impl Severity {
+	fn parse(word : &str) -> Option<Self> {
+		match word.trim().to_ascii_lowercase().as_str() {
+			"error" | "fatal error" => Some(Self::Error),
+			"warning"               => Some(Self::Warning),
+			"note" | "info"         => Some(Self::Note),
+			_                       => None,
+		}
+	}
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Severity::Error   => "error",
+			Severity::Warning => "warning",
+			Severity::Note    => "note",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	pub stage       : Stage,
+	pub severity    : Severity,
+	pub file        : Option<String>,
+	pub line        : Option<usize>,
+	pub column      : Option<usize>,
+	pub message     : String,
+	// text of the offending line, when the source is known
+	pub source_line : Option<String>,
+}
+
+impl Diagnostic {
+	pub fn is_error(&self) -> bool {
+		self.severity == Severity::Error
+	}
+}
+
+
+// "default_severity" is used for lines that don't say, e.g. linker messages of a failed link
+pub fn parse_log(log : &str, stage : Stage, default_severity : Severity, preprocessed : Option<&Preprocessed>) -> Vec<Diagnostic> {
+	let mut to_return = Vec::new();
+	for line in log.lines() {
+		let line = line.trim_matches(|c : char| c.is_whitespace() || c == '\0');
+		if line.is_empty() {
+			continue;
+		}
+
+		let parsed = parse_mesa(line)
+			.or_else(|| parse_nvidia(line))
+			.or_else(|| parse_amd(line));
+
+		let diagnostic = match parsed {
+			Some(Parsed { file, line, column, severity, message }) => {
+				let (file_name, source_line) = match preprocessed {
+					Some(preprocessed) => (
+						preprocessed.file_name(file).map(str::to_string),
+						preprocessed.line_text(file, line).map(str::to_string),
+					),
+					None => (None, None),
+				};
+				Diagnostic {
+					stage, severity,
+					file   : file_name,
+					line   : Some(line),
+					column,
+					message,
+					source_line,
+				}
+			},
+			None => {
+				let (severity, message) = match line.split_once(':') {
+					Some((word, message)) => match Severity::parse(word) {
+						Some(severity) => (severity, message.trim()),
+						None           => (default_severity, line),
+					},
+					None => (default_severity, line),
+				};
+				Diagnostic {
+					stage, severity,
+					file   : None,
+					line   : None,
+					column : None,
+					message : message.to_string(),
+					source_line : None,
+				}
+			},
+		};
+		to_return.push(diagnostic);
+	}
+	to_return
+}
+
+
+struct Parsed {
+	file     : usize,
+	line     : usize,
+	column   : Option<usize>,
+	severity : Severity,
+	message  : String,
+}
+
+// "0:12(5): error: message"
+fn parse_mesa(line : &str) -> Option<Parsed> {
+	let (file, rest) = line.split_once(':')?;
+	let file = file.trim().parse().ok()?;
+	let (line_no, rest) = rest.split_once('(')?;
+	let line_no = line_no.parse().ok()?;
+	let (column, rest) = rest.split_once(')')?;
+	let column = column.parse().ok()?;
+	let (severity, message) = rest.strip_prefix(':')?.split_once(':')?;
+	Some(Parsed {
+		file,
+		line     : line_no,
+		column   : Some(column),
+		severity : Severity::parse(severity)?,
+		message  : message.trim().to_string(),
+	})
+}
+
+// "0(12) : error C0000: message"
+fn parse_nvidia(line : &str) -> Option<Parsed> {
+	let (file, rest) = line.split_once('(')?;
+	let file = file.trim().parse().ok()?;
+	let (line_no, rest) = rest.split_once(')')?;
+	let line_no = line_no.parse().ok()?;
+	let (severity, message) = rest.trim_start().strip_prefix(':')?.split_once(':')?;
+	// severity is followed by an error code
+	let severity = severity.split_whitespace().next()?;
+	Some(Parsed {
+		file,
+		line     : line_no,
+		column   : None,
+		severity : Severity::parse(severity)?,
+		message  : message.trim().to_string(),
+	})
+}
+
+// "ERROR: 0:12: message"
+fn parse_amd(line : &str) -> Option<Parsed> {
+	let (severity, rest) = line.split_once(':')?;
+	let severity = Severity::parse(severity)?;
+	let (file, rest) = rest.split_once(':')?;
+	let file = file.trim().parse().ok()?;
+	let (line_no, message) = rest.split_once(':')?;
+	let line_no = line_no.trim().parse().ok()?;
+	Some(Parsed {
+		file,
+		line     : line_no,
+		column   : None,
+		severity,
+		message  : message.trim().to_string(),
+	})
+}
+
+
+// rendered after rustc:
+//
+// error: 'foo' : undeclared identifier
+//   --> resources/shaders/jug/frag.glsl:12:5
+//    |
+// 12 |     foo = 1.0;
+//    |     ^
+//    = in fragment shader
+impl fmt::Display for Diagnostic {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{}: {}", self.severity, self.message)?;
+
+		let line_no = self.line.map(|line| line.to_string()).unwrap_or_default();
+		let margin  = " ".repeat(line_no.len());
+		if let Some(line) = self.line {
+			let file = self.file.as_deref().unwrap_or("<source>");
+			match self.column {
+				Some(column) => writeln!(f, "{}--> {}:{}:{}", margin, file, line, column)?,
+				None         => writeln!(f, "{}--> {}:{}", margin, file, line)?,
+			}
+		}
+		if let Some(source_line) = &self.source_line {
+			writeln!(f, "{} |", margin)?;
+			writeln!(f, "{} | {}", line_no, source_line)?;
+			if let Some(column) = self.column {
+				// columns start at 1, tabs are kept so that the caret lines up
+				let padding : String = source_line
+					.chars()
+					.take(column.saturating_sub(1))
+					.map(|c| if c == '\t' { '\t' } else { ' ' })
+					.collect();
+				writeln!(f, "{} | {}^", margin, padding)?;
+			}
+		}
+		write!(f, "{} = in {}", margin, self.stage)
+	}
+}
+
+pub struct DisplayDiagnostics<'a>(pub &'a [Diagnostic]);
+
+impl<'a> fmt::Display for DisplayDiagnostics<'a> {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, diagnostic) in self.0.iter().enumerate() {
+			if i != 0 {
+				writeln!(f)?;
+				writeln!(f)?;
+			}
+			write!(f, "{}", diagnostic)?;
+		}
+		Ok(())
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mesa_log() {
+		let log = "0:12(5): error: `foo' undeclared\n0:3(1): warning: extension not supported\n";
+		let diagnostics = parse_log(log, Stage::Fragment, Severity::Error, None);
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(diagnostics[0].line, Some(12));
+		assert_eq!(diagnostics[0].column, Some(5));
+		assert_eq!(diagnostics[0].severity, Severity::Error);
+		assert_eq!(diagnostics[0].message, "`foo' undeclared");
+		assert_eq!(diagnostics[1].severity, Severity::Warning);
+	}
+
+	#[test]
+	fn nvidia_log() {
+		let log = "0(7) : error C1008: undefined variable \"foo\"\n0(9) : warning C7050: \"bar\" might be used before being initialized";
+		let diagnostics = parse_log(log, Stage::Vertex, Severity::Error, None);
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(diagnostics[0].line, Some(7));
+		assert_eq!(diagnostics[0].column, None);
+		assert_eq!(diagnostics[0].message, "undefined variable \"foo\"");
+		assert_eq!(diagnostics[1].severity, Severity::Warning);
+	}
+
+	#[test]
+	fn amd_log() {
+		let log = "ERROR: 0:4: 'foo' : undeclared identifier \nERROR: 1 compilation errors.  No code generated.\n\0";
+		let diagnostics = parse_log(log, Stage::Geometry, Severity::Error, None);
+		assert_eq!(diagnostics.len(), 2);
+		assert_eq!(diagnostics[0].line, Some(4));
+		assert_eq!(diagnostics[0].message, "'foo' : undeclared identifier");
+		assert_eq!(diagnostics[1].line, None);
+		assert_eq!(diagnostics[1].message, "1 compilation errors.  No code generated.");
+	}
+
+	#[test]
+	fn unlocated_link_message() {
+		let log = "error: vertex shader output `f_color' not read by fragment shader\nlinker message";
+		let diagnostics = parse_log(log, Stage::Link, Severity::Warning, None);
+		assert_eq!(diagnostics[0].severity, Severity::Error);
+		assert_eq!(diagnostics[0].line, None);
+		assert_eq!(diagnostics[1].severity, Severity::Warning);
+		assert_eq!(diagnostics[1].message, "linker message");
+	}
+
+	#[test]
+	fn locations_map_to_included_files() {
+		let preprocessed = Preprocessor::new()
+			.virtual_file("common.glsl", "float f() {\n\treturn foo;\n}")
+			.virtual_file("main.glsl", "#version 330 core\n#include \"common.glsl\"\nvoid main() {}")
+			.process_file("main.glsl")
+			.unwrap();
+		let diagnostics = parse_log("1:2(9): error: `foo' undeclared", Stage::Fragment, Severity::Error, Some(&preprocessed));
+		assert_eq!(diagnostics[0].file.as_deref(), Some("common.glsl"));
+		assert_eq!(diagnostics[0].source_line.as_deref(), Some("\treturn foo;"));
+
+		let rendered = diagnostics[0].to_string();
+		assert_eq!(rendered, [
+			"error: `foo' undeclared",
+			" --> common.glsl:2:9",
+			"  |",
+			"2 | \treturn foo;",
+			"  | \t       ^",
+			"  = in fragment shader",
+		].join("\n"));
+	}
+}
@@ -0,0 +1,451 @@
+use std::cell::Cell;
+use std::ffi::c_void;
+
+use gl::types::*;
+
+use crate::attributes::*;
+use crate::capabilities::{self, Feature};
+
+
+
+// -- DIRECT STATE ACCESS
+// with GL 4.5 or ARB_direct_state_access, objects are edited through their name (glNamedBufferData...)
+// rather than bound to a target, edited and unbound, which would change the user's bindings
+// "GLWrap" turns it on when available ; every function here falls back to bind-to-edit otherwise
+//
+// objects must then be created with glCreate* rather than glGen*, as a generated name only
+// becomes an object once bound
+//
+// per thread, like GL contexts
+
+
+thread_local! {
+	static ENABLED : Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn is_enabled() -> bool {
+	ENABLED.get()
+}
+
+pub(crate) fn set_enabled(enabled : bool) {
+	ENABLED.set(enabled)
+}
+
+// needs the capabilities of the current context, see "GLWrap"
+pub fn is_supported() -> bool {
+	let entry_points_loaded =
+		gl::CreateBuffers::is_loaded()          && gl::NamedBufferData::is_loaded()   &&
+		gl::CreateTextures::is_loaded()         && gl::TextureStorage2D::is_loaded()  &&
+		gl::CreateFramebuffers::is_loaded()     && gl::NamedFramebufferTexture::is_loaded() &&
+		gl::CreateRenderbuffers::is_loaded()    && gl::NamedRenderbufferStorageMultisample::is_loaded() &&
+		gl::CreateVertexArrays::is_loaded()     && gl::VertexArrayAttribFormat::is_loaded();
+	if !entry_points_loaded {
+		return false;
+	}
+	capabilities::current().is_some_and(|capabilities| capabilities.supports(Feature::DirectStateAccess))
+}
+
+
+
+// -- BUFFERS
+// "target" is only used by the bind-to-edit path
+
+
+pub(crate) fn create_buffer() -> GLuint {
+	let mut id = 0;
+	unsafe {
+		if is_enabled() { gl::CreateBuffers(1, &mut id) }
+		else            { gl::GenBuffers(1, &mut id)    }
+	}
+	id
+}
+
+pub(crate) unsafe fn buffer_data(target : GLenum, id : GLuint, size : usize, data : *const c_void, usage : GLenum) {
+	if is_enabled() {
+		gl::NamedBufferData(id, size as GLsizeiptr, data, usage);
+	}
+	else {
+		gl::BindBuffer(target, id);
+		gl::BufferData(target, size as GLsizeiptr, data, usage);
+		gl::BindBuffer(target, 0);
+	}
+}
+
+pub(crate) unsafe fn buffer_sub_data(target : GLenum, id : GLuint, offset : usize, size : usize, data : *const c_void) {
+	if is_enabled() {
+		gl::NamedBufferSubData(id, offset as GLintptr, size as GLsizeiptr, data);
+	}
+	else {
+		gl::BindBuffer(target, id);
+		gl::BufferSubData(target, offset as GLintptr, size as GLsizeiptr, data);
+		gl::BindBuffer(target, 0);
+	}
+}
+
+pub(crate) unsafe fn get_buffer_sub_data(id : GLuint, offset : usize, size : usize, data : *mut c_void) {
+	if is_enabled() {
+		gl::GetNamedBufferSubData(id, offset as GLintptr, size as GLsizeiptr, data);
+	}
+	else {
+		gl::BindBuffer(gl::COPY_READ_BUFFER, id);
+		gl::GetBufferSubData(gl::COPY_READ_BUFFER, offset as GLintptr, size as GLsizeiptr, data);
+		gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+	}
+}
+
+pub(crate) fn buffer_size(target : GLenum, id : GLuint) -> usize {
+	let mut size : GLint64 = 0;
+	unsafe {
+		if is_enabled() {
+			gl::GetNamedBufferParameteri64v(id, gl::BUFFER_SIZE, &mut size);
+		}
+		else {
+			gl::BindBuffer(target, id);
+			gl::GetBufferParameteri64v(target, gl::BUFFER_SIZE, &mut size);
+			gl::BindBuffer(target, 0);
+		}
+	}
+	size as usize
+}
+
+pub(crate) unsafe fn copy_buffer_sub_data(src : GLuint, dst : GLuint, src_offset : usize, dst_offset : usize, size : usize) {
+	if is_enabled() {
+		gl::CopyNamedBufferSubData(src, dst, src_offset as GLintptr, dst_offset as GLintptr, size as GLsizeiptr);
+	}
+	else {
+		gl::BindBuffer(gl::COPY_READ_BUFFER,  src);
+		gl::BindBuffer(gl::COPY_WRITE_BUFFER, dst);
+		gl::CopyBufferSubData(
+			gl::COPY_READ_BUFFER,
+			gl::COPY_WRITE_BUFFER,
+			src_offset as GLintptr,
+			dst_offset as GLintptr,
+			size as GLsizeiptr,
+		);
+		gl::BindBuffer(gl::COPY_READ_BUFFER,  0);
+		gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+	}
+}
+
+pub(crate) unsafe fn buffer_storage(target : GLenum, id : GLuint, size : usize, data : *const c_void, flags : GLbitfield) {
+	if is_enabled() {
+		gl::NamedBufferStorage(id, size as GLsizeiptr, data, flags);
+	}
+	else {
+		gl::BindBuffer(target, id);
+		gl::BufferStorage(target, size as GLsizeiptr, data, flags);
+		gl::BindBuffer(target, 0);
+	}
+}
+
+pub(crate) unsafe fn map_buffer_range(target : GLenum, id : GLuint, offset : usize, size : usize, access : GLbitfield) -> *mut c_void {
+	if is_enabled() {
+		gl::MapNamedBufferRange(id, offset as GLintptr, size as GLsizeiptr, access)
+	}
+	else {
+		gl::BindBuffer(target, id);
+		let ptr = gl::MapBufferRange(target, offset as GLintptr, size as GLsizeiptr, access);
+		gl::BindBuffer(target, 0);
+		ptr
+	}
+}
+
+pub(crate) unsafe fn unmap_buffer(target : GLenum, id : GLuint) {
+	if is_enabled() {
+		gl::UnmapNamedBuffer(id);
+	}
+	else {
+		gl::BindBuffer(target, id);
+		gl::UnmapBuffer(target);
+		gl::BindBuffer(target, 0);
+	}
+}
+
+
+
+// -- TEXTURES (2D only, as in texture.rs)
+
+
+pub(crate) fn create_texture() -> GLuint {
+	let mut id = 0;
+	unsafe {
+		if is_enabled() { gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id) }
+		else            { gl::GenTextures(1, &mut id)                   }
+	}
+	id
+}
+
+// DSA storage is immutable and needs a sized format, hence both formats
+// "pixels" may be null to only allocate
+#[allow(clippy::too_many_arguments)]
+pub(crate) unsafe fn texture_image_2d(
+	id              : GLuint,
+	internal_format : GLenum,
+	sized_format    : GLenum,
+	width           : u32,
+	height          : u32,
+	format          : GLenum,
+	pixel_type      : GLenum,
+	pixels          : *const c_void,
+) {
+	if is_enabled() {
+		gl::TextureStorage2D(id, 1, sized_format, width as GLsizei, height as GLsizei);
+		if !pixels.is_null() {
+			gl::TextureSubImage2D(id, 0, 0, 0, width as GLsizei, height as GLsizei, format, pixel_type, pixels);
+		}
+	}
+	else {
+		gl::BindTexture(gl::TEXTURE_2D, id);
+		gl::TexImage2D(
+			gl::TEXTURE_2D,
+			0,
+			internal_format as GLint,
+			width  as GLint,
+			height as GLint,
+			0,
+			format,
+			pixel_type,
+			pixels,
+		);
+		gl::BindTexture(gl::TEXTURE_2D, 0);
+	}
+}
+
+pub(crate) fn texture_parameter(id : GLuint, name : GLenum, value : GLint) {
+	unsafe {
+		if is_enabled() {
+			gl::TextureParameteri(id, name, value);
+		}
+		else {
+			gl::BindTexture(gl::TEXTURE_2D, id);
+			gl::TexParameteri(gl::TEXTURE_2D, name, value);
+			gl::BindTexture(gl::TEXTURE_2D, 0);
+		}
+	}
+}
+
+// "pixels" is an offset in the bound pixel pack buffer if any
+pub(crate) unsafe fn get_texture_image(id : GLuint, format : GLenum, pixel_type : GLenum, size : usize, pixels : *mut c_void) {
+	if is_enabled() {
+		gl::GetTextureImage(id, 0, format, pixel_type, size as GLsizei, pixels);
+	}
+	else {
+		gl::BindTexture(gl::TEXTURE_2D, id);
+		gl::GetTexImage(gl::TEXTURE_2D, 0, format, pixel_type, pixels);
+		gl::BindTexture(gl::TEXTURE_2D, 0);
+	}
+}
+
+
+
+// -- FRAMEBUFFERS
+
+
+pub(crate) fn create_framebuffer() -> GLuint {
+	let mut id = 0;
+	unsafe {
+		if is_enabled() { gl::CreateFramebuffers(1, &mut id) }
+		else            { gl::GenFramebuffers(1, &mut id)    }
+	}
+	id
+}
+
+pub(crate) fn framebuffer_texture(framebuffer : GLuint, attachment : GLenum, texture : GLuint) {
+	unsafe {
+		if is_enabled() {
+			gl::NamedFramebufferTexture(framebuffer, attachment, texture, 0);
+		}
+		else {
+			gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+			gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture, 0);
+			gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+		}
+	}
+}
+
+pub(crate) fn framebuffer_renderbuffer(framebuffer : GLuint, attachment : GLenum, renderbuffer : GLuint) {
+	unsafe {
+		if is_enabled() {
+			gl::NamedFramebufferRenderbuffer(framebuffer, attachment, gl::RENDERBUFFER, renderbuffer);
+		}
+		else {
+			gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+			gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, renderbuffer);
+			gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+		}
+	}
+}
+
+pub(crate) fn framebuffer_status(framebuffer : GLuint) -> GLenum {
+	unsafe {
+		if is_enabled() {
+			gl::CheckNamedFramebufferStatus(framebuffer, gl::FRAMEBUFFER)
+		}
+		else {
+			gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+			let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+			gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+			status
+		}
+	}
+}
+
+
+
+// -- RENDERBUFFERS
+
+
+pub(crate) fn create_renderbuffer() -> GLuint {
+	let mut id = 0;
+	unsafe {
+		if is_enabled() { gl::CreateRenderbuffers(1, &mut id) }
+		else            { gl::GenRenderbuffers(1, &mut id)    }
+	}
+	id
+}
+
+// unlike textures, renderbuffer storage stays mutable with DSA, so it can be reallocated on resize
+// "samples" of 0 means no multisampling
+pub(crate) fn renderbuffer_storage(id : GLuint, samples : u32, sized_format : GLenum, width : u32, height : u32) {
+	unsafe {
+		if is_enabled() {
+			gl::NamedRenderbufferStorageMultisample(id, samples as GLsizei, sized_format, width as GLsizei, height as GLsizei);
+		}
+		else {
+			gl::BindRenderbuffer(gl::RENDERBUFFER, id);
+			gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as GLsizei, sized_format, width as GLsizei, height as GLsizei);
+			gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+		}
+	}
+}
+
+
+
+// -- VERTEX ARRAYS
+
+
+pub(crate) fn create_vertex_array() -> GLuint {
+	let mut id = 0;
+	unsafe {
+		if is_enabled() { gl::CreateVertexArrays(1, &mut id) }
+		else            { gl::GenVertexArrays(1, &mut id)    }
+	}
+	id
+}
+
+// the attribute at "location" reads "data_info" elements from "buffer"
+// a "stride" of 0 means tightly packed elements
+pub(crate) fn vertex_attribute(vao : GLuint, location : GLuint, buffer : GLuint, stride : usize, offset : usize, data_info : &GPUInfo) {
+	let GPUInfo { n_components, gl_type } = data_info;
+	let n_components = *n_components;
+	unsafe {
+		if is_enabled() {
+			// unlike glVertexAttribPointer, a stride of 0 means no stride at all
+			let stride = if stride == 0 { data_info.size() } else { stride };
+			// one binding point per attribute
+			gl::VertexArrayVertexBuffer(vao, location, buffer, offset as GLintptr, stride as GLsizei);
+			if gl_type.is_integer() {
+				gl::VertexArrayAttribIFormat(vao, location, n_components as GLint, gl_type.to_opengl_sym(), 0);
+			}
+			else {
+				gl::VertexArrayAttribFormat(vao, location, n_components as GLint, gl_type.to_opengl_sym(), gl::FALSE, 0);
+			}
+			gl::VertexArrayAttribBinding(vao, location, location);
+			gl::EnableVertexArrayAttrib(vao, location);
+		}
+		else {
+			gl::BindVertexArray(vao);
+			gl::BindBuffer(gl::ARRAY_BUFFER, buffer);
+			if gl_type.is_integer() {
+				gl::VertexAttribIPointer(
+					location,
+					n_components as GLint,
+					gl_type.to_opengl_sym(),
+					stride as GLsizei,
+					offset as *const _,
+				);
+			}
+			else {
+				gl::VertexAttribPointer(
+					location,
+					n_components as GLint,
+					gl_type.to_opengl_sym(),
+					gl::FALSE,
+					stride as GLsizei,
+					offset as *const _,
+				);
+			}
+			gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+			gl::EnableVertexAttribArray(location);
+			gl::BindVertexArray(0);
+		}
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::buffer::BufferBld;
+	use crate::frame::FrameBufferBuilder;
+	use crate::program::ProgramBuilder;
+	use crate::renderbuffer::Renderbuffer;
+	use crate::shader::*;
+	use crate::texture::{TexFormat, Texture};
+	use crate::trace::*;
+
+	#[test]
+	fn objects_are_created_and_edited_by_name_with_gl_4_5() {
+		let recorder = Recorder::new()
+			.with_integer(gl::MAJOR_VERSION, 4)
+			.with_integer(gl::MINOR_VERSION, 5);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+		assert!(is_enabled());
+
+		let buffer = BufferBld::array().data(&[0.0f32; 8]).unwrap();
+		assert_eq!(trace.named("CreateBuffers").len(), 1);
+		assert_eq!(trace.named("NamedBufferData")[0].args[0], Value::of(buffer.id().0));
+
+		let color = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		let depth = Renderbuffer::new(4, 4, TexFormat::Depth).unwrap();
+		let _framebuffer = FrameBufferBuilder::new().attach_color(&color).attach_depth(&depth).build().unwrap();
+		assert_eq!(trace.named("CreateTextures").len(), 1);
+		assert_eq!(trace.named("TextureStorage2D")[0].args[0], Value::of(color.id.0));
+		assert_eq!(trace.named("NamedRenderbufferStorageMultisample")[0].args[0], Value::of(depth.id.0));
+		assert_eq!(trace.named("CreateFramebuffers").len(), 1);
+		assert_eq!(trace.named("NamedFramebufferTexture")[0].args[2], Value::of(color.id.0));
+		assert_eq!(trace.named("NamedFramebufferRenderbuffer")[0].args[3], Value::of(depth.id.0));
+
+		let vert = Shader::<Vertex>::from_str("void main() {}").unwrap();
+		let frag = Shader::<Fragment>::from_str("void main() {}").unwrap();
+		let program = ProgramBuilder::new(vert, frag).attributes(&["position"]).build().unwrap();
+		program.bind("position", buffer.direct_view().unwrap()).unwrap();
+		assert_eq!(trace.named("CreateVertexArrays").len(), 1);
+		assert_eq!(trace.named("VertexArrayVertexBuffer")[0].args[2], Value::of(buffer.id().0));
+		assert_eq!(trace.named("VertexArrayAttribFormat").len(), 1);
+
+		// nothing was bound to be edited
+		for name in ["GenBuffers", "BindBuffer", "GenTextures", "BindTexture", "GenFramebuffers", "BindFramebuffer", "GenRenderbuffers", "GenVertexArrays"] {
+			assert!(trace.named(name).is_empty(), "{} was called", name);
+		}
+	}
+
+	#[test]
+	fn bind_to_edit_is_used_before_gl_4_5() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+		assert!(!is_enabled());
+
+		let _buffer = BufferBld::array().data(&[0.0f32; 8]).unwrap();
+		assert_eq!(trace.named("GenBuffers").len(), 1);
+		assert!(trace.named("CreateBuffers").is_empty());
+		assert!(trace.named("NamedBufferData").is_empty());
+	}
+}
@@ -1,3 +1,11 @@
+use std::cell::Cell;
+use std::fmt;
+use std::panic::Location;
+
+use gl::types::GLenum;
+
+use crate::capabilities::Feature;
+use crate::diagnostics::{Diagnostic, DisplayDiagnostics};
 
 #[derive(Debug)]
 pub enum GLError {
@@ -6,22 +14,63 @@ pub enum GLError {
 	CouldNotCreateVAO,
 	IsntUniformBuffer,
 	ImageTypeNotImplemented,
-	LinkProgram(String),
-	CompileError(String),
+	LinkProgram(Vec<Diagnostic>),
+	CompileError(Vec<Diagnostic>),
 	FileError(std::io::Error),
 	InexistentOrUndeclaredAttribute(String),
+	MismatchedAttributeType(String),
 	InexistentUniform(String),
+	MismatchedUniformType {
+		gl_type   : GLenum,
+		rust_type : &'static str,
+	},
 	InexistentUniformBuffer(String),
 	UnregisteredVAO,
 	NoBufferAttached,
+	NoIndexBufferAttached,
+	DrawRangeOutOfBounds,
+	IsntDrawIndirectBuffer,
+	CouldNotCreateQuery,
+	WrongQueryKind,
+	CouldNotCreateFence,
+	FenceWaitFailed,
+	FenceTimeout,
+	CouldNotMapBuffer,
+	InvalidMapRange,
+	InvalidMapAccess,
+	BufferOverflow,
+	BufferRangeOutOfBounds,
+	OverlappingCopy,
+	MismatchedBufferTypes,
+	BufferViewOutOfRange,
+	IncludeNotFound(String),
+	IncludeCycle(String),
+	TooManyFeatures(usize),
+	CouldNotCreatePipeline,
+	ProgramNotSeparable,
+	MissingProgramStage,
+	InvalidPipeline(String),
 	CannotGetAttributeCountOnProgram,
 	AttributeNameTooLong,
 	AttributeNameEncodingError,
 	BufferTooSmallForConversion,
 	TooManyTextures,
+	OverlappingTextureUnit(usize),
 	CannotGetMaxTexUnits,
 	CouldNotCreateFrameBuffer,
+	CouldNotCreateRenderbuffer,
+	TooManySamples,
 	IncompleteFrameBuffer(FrameBufferStatus),
+	UnknownFrameBufferStatus(GLenum),
+	NameContainsNul(String),
+	FeedbackNeedsSeveralBuffers(usize),
+	Unsupported(Feature),
+	// reported by glGetError after "call", see "check"
+	Driver {
+		code     : GLenum,
+		call     : &'static str,
+		location : &'static Location<'static>,
+	},
 }
 
 
@@ -29,6 +78,76 @@ impl From<std::io::Error> for GLError {
 	fn from(err: std::io::Error) -> Self { Self::FileError(err) }
 }
 
+impl fmt::Display for GLError {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::CouldNotCreateBuffer                  => write!(f, "could not create buffer"),
+			Self::CouldNotCreateTexture                 => write!(f, "could not create texture"),
+			Self::CouldNotCreateVAO                     => write!(f, "could not create vertex array"),
+			Self::IsntUniformBuffer                     => write!(f, "buffer isn't a uniform buffer"),
+			Self::ImageTypeNotImplemented               => write!(f, "image type not implemented"),
+			Self::LinkProgram(diagnostics)              => write!(f, "could not link program\n{}", DisplayDiagnostics(diagnostics)),
+			Self::CompileError(diagnostics)             => write!(f, "could not compile shader\n{}", DisplayDiagnostics(diagnostics)),
+			Self::FileError(err)                        => write!(f, "file error: {}", err),
+			Self::InexistentOrUndeclaredAttribute(name) => write!(f, "attribute \"{}\" doesn't exist or wasn't declared", name),
+			Self::MismatchedAttributeType(name)         => write!(f, "buffer view doesn't match the type of attribute \"{}\"", name),
+			Self::InexistentUniform(name)               => write!(f, "uniform \"{}\" doesn't exist", name),
+			Self::MismatchedUniformType { gl_type, rust_type } => write!(f, "uniform of GL type {:#x} can't be set from {}", gl_type, rust_type),
+			Self::InexistentUniformBuffer(name)         => write!(f, "uniform block \"{}\" doesn't exist", name),
+			Self::UnregisteredVAO                       => write!(f, "vertex array isn't registered"),
+			Self::NoBufferAttached                      => write!(f, "no buffer attached"),
+			Self::NoIndexBufferAttached                 => write!(f, "no index buffer attached"),
+			Self::DrawRangeOutOfBounds                  => write!(f, "draw range out of bounds"),
+			Self::IsntDrawIndirectBuffer                => write!(f, "buffer isn't a draw indirect buffer"),
+			Self::CouldNotCreateQuery                   => write!(f, "could not create query"),
+			Self::WrongQueryKind                        => write!(f, "wrong query kind"),
+			Self::CouldNotCreateFence                   => write!(f, "could not create fence"),
+			Self::FenceWaitFailed                       => write!(f, "waiting on fence failed"),
+			Self::FenceTimeout                          => write!(f, "fence wait timed out"),
+			Self::CouldNotMapBuffer                     => write!(f, "could not map buffer"),
+			Self::InvalidMapRange                       => write!(f, "invalid map range"),
+			Self::InvalidMapAccess                      => write!(f, "map access doesn't allow the requested use"),
+			Self::BufferOverflow                        => write!(f, "buffer overflow"),
+			Self::BufferRangeOutOfBounds                => write!(f, "buffer range out of bounds"),
+			Self::OverlappingCopy                       => write!(f, "source and destination of copy overlap"),
+			Self::MismatchedBufferTypes                 => write!(f, "mismatched buffer types"),
+			Self::BufferViewOutOfRange                  => write!(f, "buffer view out of range"),
+			Self::IncludeNotFound(path)                 => write!(f, "include \"{}\" not found", path),
+			Self::IncludeCycle(path)                    => write!(f, "include cycle through \"{}\"", path),
+			Self::TooManyFeatures(n)                    => write!(f, "too many permutations of {} features", n),
+			Self::CouldNotCreatePipeline                => write!(f, "could not create program pipeline"),
+			Self::ProgramNotSeparable                   => write!(f, "program isn't separable"),
+			Self::MissingProgramStage                   => write!(f, "program lacks a required stage"),
+			Self::InvalidPipeline(log)                  => write!(f, "invalid program pipeline: {}", log),
+			Self::CannotGetAttributeCountOnProgram      => write!(f, "cannot get the attribute count of program"),
+			Self::AttributeNameTooLong                  => write!(f, "attribute name too long"),
+			Self::AttributeNameEncodingError            => write!(f, "attribute name isn't valid UTF-8"),
+			Self::BufferTooSmallForConversion           => write!(f, "buffer too small for conversion"),
+			Self::TooManyTextures                       => write!(f, "too many textures"),
+			Self::OverlappingTextureUnit(unit)          => write!(f, "programs of the pipeline set texture unit {} to different textures", unit),
+			Self::CannotGetMaxTexUnits                  => write!(f, "cannot get the maximum number of texture units"),
+			Self::CouldNotCreateFrameBuffer             => write!(f, "could not create framebuffer"),
+			Self::CouldNotCreateRenderbuffer            => write!(f, "could not create renderbuffer"),
+			Self::TooManySamples                        => write!(f, "more samples than the context supports"),
+			Self::IncompleteFrameBuffer(status)         => write!(f, "incomplete framebuffer: {}", status),
+			Self::UnknownFrameBufferStatus(status)      => write!(f, "unknown framebuffer status {:#x}", status),
+			Self::NameContainsNul(name)                 => write!(f, "name \"{}\" contains a NUL byte", name),
+			Self::FeedbackNeedsSeveralBuffers(n)        => write!(f, "separate transform feedback writes to {} buffers, only one can be captured into", n),
+			Self::Unsupported(feature)                  => write!(f, "unsupported by the context: {}", feature),
+			Self::Driver { code, call, location }       => write!(f, "{} in {} at {}", error_name(*code), call, location),
+		}
+	}
+}
+
+impl std::error::Error for GLError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::FileError(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
 #[derive(Debug)]
 pub enum FrameBufferStatus {
 	Undefined,
@@ -73,4 +192,101 @@ impl FrameBufferStatus {
 	} 
 }
 
+impl fmt::Display for FrameBufferStatus {
+	fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			FrameBufferStatus::Undefined                   => "undefined",
+			FrameBufferStatus::IncompleteAttachment        => "incomplete attachment",
+			FrameBufferStatus::IncompleteMissingAttachment => "missing attachment",
+			FrameBufferStatus::IncompleteDrawBuffer        => "incomplete draw buffer",
+			FrameBufferStatus::IncompleteReadBuffer        => "incomplete read buffer",
+			FrameBufferStatus::AttachmentObjectType        => "wrong attachment object type",
+			FrameBufferStatus::Unsupported                 => "unsupported combination of formats",
+			FrameBufferStatus::IncompleteMultisample       => "mismatched sample counts",
+			FrameBufferStatus::IncompleteLayerTargets      => "mismatched layer targets",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+
+
+// -- DRIVER ERRORS
+// with checking on (see "GLWrap::set_error_checking"), wrapped calls read glGetError once done
+// and fail with "GLError::Driver", located at the caller of the wrapper thanks to #[track_caller]
+// checks are skipped in release builds ; errors raised by unwrapped calls surface at the next check
+// the setting is per thread, like GL contexts
+
+thread_local! {
+	static CHECK_ERRORS : Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn is_checking_errors() -> bool {
+	cfg!(debug_assertions) && CHECK_ERRORS.get()
+}
 
+pub(crate) fn set_checking_errors(enabled : bool) {
+	CHECK_ERRORS.set(enabled)
+}
+
+#[track_caller]
+pub(crate) fn check(call : &'static str) -> Result<(), GLError> {
+	if !is_checking_errors() {
+		return Ok(());
+	}
+	let code = unsafe {gl::GetError()};
+	if code == gl::NO_ERROR {
+		return Ok(());
+	}
+	// the driver may hold several flags, only the first one is reported
+	// bounded, as a lost context keeps reporting errors
+	for _ in 0 .. 16 {
+		if unsafe {gl::GetError()} == gl::NO_ERROR {
+			break;
+		}
+	}
+	Err(GLError::Driver { code, call, location : Location::caller() })
+}
+
+fn error_name(code : GLenum) -> String {
+	let name = match code {
+		gl::INVALID_ENUM                  => "GL_INVALID_ENUM",
+		gl::INVALID_VALUE                 => "GL_INVALID_VALUE",
+		gl::INVALID_OPERATION             => "GL_INVALID_OPERATION",
+		gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+		gl::OUT_OF_MEMORY                 => "GL_OUT_OF_MEMORY",
+		gl::STACK_UNDERFLOW               => "GL_STACK_UNDERFLOW",
+		gl::STACK_OVERFLOW                => "GL_STACK_OVERFLOW",
+		gl::CONTEXT_LOST                  => "GL_CONTEXT_LOST",
+		_ => return format!("GL error 0x{:04X}", code),
+	};
+	name.to_string()
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn driver_errors_name_code_call_and_location() {
+		let location = Location::caller();
+		let err = GLError::Driver { code : gl::INVALID_ENUM, call : "Texture::allocate", location };
+		assert_eq!(err.to_string(), format!("GL_INVALID_ENUM in Texture::allocate at {}", location));
+
+		let err = GLError::Driver { code : 0x1234, call : "Program::bind", location };
+		assert!(err.to_string().starts_with("GL error 0x1234 in Program::bind at src/error.rs:"));
+	}
+
+	#[test]
+	fn file_errors_keep_their_source() {
+		use std::error::Error;
+		let err = GLError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+		assert!(err.source().is_some());
+		assert!(GLError::NoBufferAttached.source().is_none());
+	}
+}
@@ -1,4 +1,6 @@
-use crate::{texture::Texture, error::{GLError, FrameBufferStatus}};
+use std::cell::Cell;
+
+use crate::{texture::{Texture, TexFormat}, renderbuffer::Renderbuffer, error::{GLError, FrameBufferStatus, check}, debug, dsa};
 
 #[derive(Debug)]
 pub struct FrameBufferId(gl::types::GLuint);
@@ -11,11 +13,13 @@ impl Default for FrameBufferId {
 pub struct FrameBuffer {
 	id : FrameBufferId,
 	has_depth_buffer : bool,
+	// (x, y, width, height), set by "make_current" once known
+	viewport : Cell<Option<(i32, i32, i32, i32)>>,
 }
 
 impl Default for FrameBuffer {
     fn default() -> Self {
-        Self { id: FrameBufferId::default(), has_depth_buffer: true }
+        Self { id: FrameBufferId::default(), has_depth_buffer: true, viewport: Cell::new(None) }
     }
 }
 
@@ -28,79 +32,426 @@ impl FrameBuffer {
 	}
 
 
+	// also sets the viewport to the one of this framebuffer, so passes of different sizes can alternate
 	pub fn make_current(&self) {
 		unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, self.id.0); }
+		if let Some((x, y, w, h)) = self.viewport.get() {
+			unsafe { gl::Viewport(x, y, w, h); }
+		}
+	}
+
+	pub fn viewport(&self) -> Option<(i32, i32, i32, i32)> { self.viewport.get() }
+
+	// applied by the next "make_current"
+	// the size of the attachments for built framebuffers, see "GLWrap::set_viewport" for the default one
+	pub fn set_viewport(&self, x : i32, y : i32, w : i32, h : i32) {
+		self.viewport.set(Some((x, y, w, h)));
 	}
 }
 
 
+// what a framebuffer renders into : textures can be sampled afterwards, renderbuffers can't
+#[derive(Debug, Clone, Copy)]
+pub enum Attachment<'a> {
+	Texture(&'a Texture),
+	Renderbuffer(&'a Renderbuffer),
+}
+
+impl<'a> From<&'a Texture> for Attachment<'a> {
+	fn from(texture: &'a Texture) -> Self { Self::Texture(texture) }
+}
+
+impl<'a> From<&'a Renderbuffer> for Attachment<'a> {
+	fn from(renderbuffer: &'a Renderbuffer) -> Self { Self::Renderbuffer(renderbuffer) }
+}
+
+impl Attachment<'_> {
+	fn attach_to(self, framebuffer: gl::types::GLuint, attachment: gl::types::GLenum) {
+		match self {
+			Attachment::Texture(texture)           => dsa::framebuffer_texture(framebuffer, attachment, texture.id.0),
+			Attachment::Renderbuffer(renderbuffer) => dsa::framebuffer_renderbuffer(framebuffer, attachment, renderbuffer.id.0),
+		}
+	}
+
+	fn size(self) -> (u32, u32) {
+		match self {
+			Attachment::Texture(texture)           => (texture.width, texture.height),
+			Attachment::Renderbuffer(renderbuffer) => (renderbuffer.width, renderbuffer.height),
+		}
+	}
+}
+
+// attaches what is given, and checks the framebuffer is complete
+fn attach_all(id: gl::types::GLuint, color: Option<Attachment>, depth: Option<Attachment>, stencil: Option<Attachment>) -> Result<(), GLError> {
+	if let Some(color) = color {
+		color.attach_to(id, gl::COLOR_ATTACHMENT0);
+	}
+
+	if let Some(depth) = depth {
+		depth.attach_to(id, gl::DEPTH_ATTACHMENT);
+	}
+
+	if let Some(stencil) = stencil {
+		stencil.attach_to(id, gl::STENCIL_ATTACHMENT);
+	}
+
+	let check_status = dsa::framebuffer_status(id);
+	if check_status != gl::FRAMEBUFFER_COMPLETE  {
+		// e.g. 0, when the check itself failed
+		return Err(FrameBufferStatus::from_opengl_sym(check_status)
+			.map_or(GLError::UnknownFrameBufferStatus(check_status), GLError::IncompleteFrameBuffer));
+	}
+	Ok(())
+}
+
+
 pub struct FrameBufferBuilder<'a> {
-	color_texture :   Option<&'a Texture>,
-	depth_texture :   Option<&'a Texture>,
-	stencil_texture : Option<&'a Texture>,
+	color_attachment :   Option<Attachment<'a>>,
+	depth_attachment :   Option<Attachment<'a>>,
+	stencil_attachment : Option<Attachment<'a>>,
+	label :              Option<String>,
 }
 
 impl<'a> FrameBufferBuilder<'a> {
 	pub fn new() -> Self { 
 		Self {
-			color_texture:   None,
-			depth_texture:   None,
-			stencil_texture: None,
+			color_attachment:   None,
+			depth_attachment:   None,
+			stencil_attachment: None,
+			label:              None,
 		} 
 	}
 
-	pub fn attach_color(mut self, texture: &'a Texture) -> Self {
-		self.color_texture = Some(texture);
+	// a "&Texture" or a "&Renderbuffer"
+	pub fn attach_color(mut self, attachment: impl Into<Attachment<'a>>) -> Self {
+		self.color_attachment = Some(attachment.into());
 		self
 	}
-	pub fn attach_depth(mut self, texture: &'a Texture) -> Self {
-		self.depth_texture = Some(texture);
+	pub fn attach_depth(mut self, attachment: impl Into<Attachment<'a>>) -> Self {
+		self.depth_attachment = Some(attachment.into());
 		self
 	}
-	pub fn attach_stencil(mut self, texture: &'a Texture) -> Self {
-		self.stencil_texture = Some(texture);
+	pub fn attach_stencil(mut self, attachment: impl Into<Attachment<'a>>) -> Self {
+		self.stencil_attachment = Some(attachment.into());
+		self
+	}
+	pub fn label(mut self, label: &str) -> Self {
+		self.label = Some(label.to_string());
 		self
 	}
 
+	#[track_caller]
 	pub fn build(self) -> Result<FrameBuffer, GLError> {
-		let FrameBufferBuilder { color_texture, depth_texture, stencil_texture } = self;
+		let FrameBufferBuilder { color_attachment, depth_attachment, stencil_attachment, label } = self;
 		
-		let mut id : gl::types::GLuint = 0;
-		unsafe {gl::GenFramebuffers(1, &mut id)}		
+		let id = dsa::create_framebuffer();
 		if id == 0 {
 			return Err(GLError::CouldNotCreateFrameBuffer); 
 		}
 
-		unsafe {gl::BindFramebuffer(gl::FRAMEBUFFER, id);}		
+		attach_all(id, color_attachment, depth_attachment, stencil_attachment)?;
+		if let Some(label) = label {
+			debug::set_label(gl::FRAMEBUFFER, id, &label);
+		}
+		check("FrameBufferBuilder::build")?;
 
-		if let Some(color_texture) = color_texture {
-			unsafe {
-				gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_texture.id.0, 0)
-			}
+		let size = color_attachment.or(depth_attachment).or(stencil_attachment).map(Attachment::size);
+		let viewport = size.map(|(w, h)| (0, 0, w as i32, h as i32));
+
+		Ok(FrameBuffer {
+			id: FrameBufferId(id),
+			has_depth_buffer: depth_attachment.is_some(),
+			viewport: Cell::new(viewport),
+		})		
+	}
+}
+
+
+
+// -- RENDER TARGETS
+// a framebuffer that owns its attachments, all of one size, and reallocates them on "resize"
+// e.g. for offscreen passes that follow the size of the window
+
+
+// how an attachment of a "RenderTarget" is allocated
+#[derive(Debug, Clone, Copy)]
+pub enum AttachmentSpec {
+	// to be sampled afterwards
+	Texture(TexFormat),
+	Renderbuffer(TexFormat),
+}
+
+impl AttachmentSpec {
+	#[track_caller]
+	fn allocate(self, width: u32, height: u32) -> Result<OwnedAttachment, GLError> {
+		match self {
+			AttachmentSpec::Texture(format)      => Ok(OwnedAttachment::Texture(Texture::allocate(width, height, format)?)),
+			AttachmentSpec::Renderbuffer(format) => Ok(OwnedAttachment::Renderbuffer(Renderbuffer::new(width, height, format)?)),
 		}
+	}
+}
 
-		if let Some(depth_texture) = depth_texture {
-			unsafe {
-				gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, depth_texture.id.0, 0)
-			}
+// deleted when dropped
+#[derive(Debug)]
+enum OwnedAttachment {
+	Texture(Texture),
+	Renderbuffer(Renderbuffer),
+}
+
+impl OwnedAttachment {
+	fn spec(&self) -> AttachmentSpec {
+		match self {
+			OwnedAttachment::Texture(texture)           => AttachmentSpec::Texture(texture.tex_format),
+			OwnedAttachment::Renderbuffer(renderbuffer) => AttachmentSpec::Renderbuffer(renderbuffer.format),
 		}
+	}
 
-		if let Some(stencil_texture) = stencil_texture {
-			unsafe {
-				gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::STENCIL_ATTACHMENT, gl::TEXTURE_2D, stencil_texture.id.0, 0)
-			}
+	fn as_attachment(&self) -> Attachment<'_> {
+		match self {
+			OwnedAttachment::Texture(texture)           => Attachment::Texture(texture),
+			OwnedAttachment::Renderbuffer(renderbuffer) => Attachment::Renderbuffer(renderbuffer),
 		}
+	}
 
-		let check_status = unsafe  { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
-		if check_status != gl::FRAMEBUFFER_COMPLETE  {
-			return Err(GLError::IncompleteFrameBuffer(FrameBufferStatus::from_opengl_sym(check_status).unwrap()));
+	fn texture(&self) -> Option<&Texture> {
+		match self {
+			OwnedAttachment::Texture(texture) => Some(texture),
+			OwnedAttachment::Renderbuffer(_)  => None,
 		}
+	}
 
-		unsafe {gl::BindFramebuffer(gl::FRAMEBUFFER, 0);}		
+}
 
-		Ok(FrameBuffer {
-			id: FrameBufferId(id),
-			has_depth_buffer: depth_texture.is_some(),
-		})		
+impl Drop for OwnedAttachment {
+	fn drop(&mut self) {
+		// renderbuffers delete themselves
+		if let OwnedAttachment::Texture(texture) = self {
+			unsafe { gl::DeleteTextures(1, &texture.id.0); }
+		}
+	}
+}
+
+// attachments allocated before a failure are deleted
+#[track_caller]
+fn allocate_all(specs: [Option<AttachmentSpec>; 3], width: u32, height: u32) -> Result<[Option<OwnedAttachment>; 3], GLError> {
+	let [color, depth, stencil] = specs.map(|spec| spec.map(|spec| spec.allocate(width, height)));
+	Ok([color.transpose()?, depth.transpose()?, stencil.transpose()?])
+}
+
+
+pub struct RenderTargetBuilder {
+	width :   u32,
+	height :  u32,
+	color :   Option<AttachmentSpec>,
+	depth :   Option<AttachmentSpec>,
+	stencil : Option<AttachmentSpec>,
+	label :   Option<String>,
+}
+
+impl RenderTargetBuilder {
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			color:   None,
+			depth:   None,
+			stencil: None,
+			label:   None,
+		}
+	}
+
+	pub fn color(mut self, spec: AttachmentSpec) -> Self {
+		self.color = Some(spec);
+		self
+	}
+	pub fn depth(mut self, spec: AttachmentSpec) -> Self {
+		self.depth = Some(spec);
+		self
+	}
+	pub fn stencil(mut self, spec: AttachmentSpec) -> Self {
+		self.stencil = Some(spec);
+		self
+	}
+	pub fn label(mut self, label: &str) -> Self {
+		self.label = Some(label.to_string());
+		self
+	}
+
+	#[track_caller]
+	pub fn build(self) -> Result<RenderTarget, GLError> {
+		let RenderTargetBuilder { width, height, color, depth, stencil, label } = self;
+
+		let [color, depth, stencil] = allocate_all([color, depth, stencil], width, height)?;
+
+		let mut builder = FrameBufferBuilder::new();
+		builder.color_attachment   = color.as_ref().map(OwnedAttachment::as_attachment);
+		builder.depth_attachment   = depth.as_ref().map(OwnedAttachment::as_attachment);
+		builder.stencil_attachment = stencil.as_ref().map(OwnedAttachment::as_attachment);
+		builder.label              = label;
+		let framebuffer = builder.build()?;
+		framebuffer.set_viewport(0, 0, width as i32, height as i32);
+
+		Ok(RenderTarget { framebuffer, width, height, color, depth, stencil })
+	}
+}
+
+
+#[derive(Debug)]
+pub struct RenderTarget {
+	framebuffer : FrameBuffer,
+	width :       u32,
+	height :      u32,
+	color :       Option<OwnedAttachment>,
+	depth :       Option<OwnedAttachment>,
+	stencil :     Option<OwnedAttachment>,
+}
+
+impl RenderTarget {
+	pub fn width(&self) -> u32 { self.width }
+	pub fn height(&self) -> u32 { self.height }
+
+	pub fn framebuffer(&self) -> &FrameBuffer { &self.framebuffer }
+
+	// binds the target and sets the viewport to its size
+	pub fn make_current(&self) { self.framebuffer.make_current() }
+
+	pub fn clear(&self) { self.framebuffer.clear() }
+
+	// None when the attachment is missing or a renderbuffer
+	// a new texture after each "resize" : programs sampling it must be given it again
+	pub fn color_texture(&self) -> Option<&Texture> { self.color.as_ref()?.texture() }
+	pub fn depth_texture(&self) -> Option<&Texture> { self.depth.as_ref()?.texture() }
+
+	// reallocates every attachment, their content is lost, and checks the framebuffer is still complete
+	// new attachments get new names, as the storage of textures may be immutable (see "dsa")
+	// the target is left untouched if any of them can't be allocated or attached
+	// the new viewport is applied by the next "make_current"
+	#[track_caller]
+	pub fn resize(&mut self, width: u32, height: u32) -> Result<(), GLError> {
+		if (width, height) == (self.width, self.height) {
+			return Ok(());
+		}
+		let specs = [&self.color, &self.depth, &self.stencil].map(|attachment| attachment.as_ref().map(OwnedAttachment::spec));
+		let [color, depth, stencil] = allocate_all(specs, width, height)?;
+
+		let attach = |color: &Option<OwnedAttachment>, depth: &Option<OwnedAttachment>, stencil: &Option<OwnedAttachment>| attach_all(
+			self.framebuffer.id.0,
+			color.as_ref().map(OwnedAttachment::as_attachment),
+			depth.as_ref().map(OwnedAttachment::as_attachment),
+			stencil.as_ref().map(OwnedAttachment::as_attachment),
+		);
+		if let Err(error) = attach(&color, &depth, &stencil).and_then(|()| check("RenderTarget::resize")) {
+			// the old attachments were complete
+			let _ = attach(&self.color, &self.depth, &self.stencil);
+			return Err(error);
+		}
+
+		// the old attachments are deleted once replaced
+		self.color   = color;
+		self.depth   = depth;
+		self.stencil = stencil;
+		self.width   = width;
+		self.height  = height;
+		self.framebuffer.set_viewport(0, 0, width as i32, height as i32);
+		Ok(())
+	}
+}
+
+impl Drop for RenderTarget {
+	fn drop(&mut self) {
+		// the attachments are deleted afterwards, when dropped
+		unsafe { gl::DeleteFramebuffers(1, &self.framebuffer.id.0); }
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::texture::TexFormat;
+	use crate::trace::*;
+
+	#[test]
+	fn depth_texture_is_attached_at_depth_attachment() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let color = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		let depth = Texture::allocate(4, 4, TexFormat::Depth).unwrap();
+		trace.clear();
+		let framebuffer = FrameBufferBuilder::new().attach_color(&color).attach_depth(&depth).build().unwrap();
+		assert!(framebuffer.has_depth_buffer);
+
+		let attachments = trace.named("FramebufferTexture2D");
+		let depth_attachment = attachments
+			.iter()
+			.find(|call| call.args[3] == Value::of(depth.id.0))
+			.expect("depth texture not attached");
+		assert_eq!(depth_attachment.args[1], Value::of(gl::DEPTH_ATTACHMENT));
+		assert_eq!(attachments.len(), 2);
+	}
+
+	#[test]
+	fn render_target_resize_reallocates_and_follows_viewport() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let gl       = GLWrap::with_backend(recorder);
+		gl.set_viewport(0, 0, 640, 480);
+
+		let mut target = RenderTargetBuilder::new(4, 4)
+			.color(AttachmentSpec::Texture(TexFormat::Rgba))
+			.depth(AttachmentSpec::Renderbuffer(TexFormat::Depth))
+			.build()
+			.unwrap();
+		let old_color = target.color_texture().unwrap().id.0;
+		assert!(target.depth_texture().is_none());
+
+		trace.clear();
+		target.resize(8, 6).unwrap();
+		let new_color = target.color_texture().unwrap().id.0;
+		assert_ne!(new_color, old_color);
+		assert_eq!(trace.named("DeleteTextures").len(), 1);
+		assert_eq!(trace.named("DeleteRenderbuffers").len(), 1);
+		// every new attachment is allocated before the old ones are deleted
+		let calls = trace.calls();
+		let last_allocation = calls.iter().rposition(|call| call.name.starts_with("Gen")).unwrap();
+		let first_deletion  = calls.iter().position(|call| call.name.starts_with("Delete")).unwrap();
+		assert!(last_allocation < first_deletion);
+		assert_eq!(trace.named("FramebufferTexture2D")[0].args[3], Value::of(new_color));
+		assert_eq!(trace.named("RenderbufferStorageMultisample")[0].args[3..], [Value::of(8), Value::of(6)]);
+		assert_eq!(trace.named("CheckFramebufferStatus").len(), 1);
+
+		let viewport = |width : i32, height : i32| vec![Value::of(0), Value::of(0), Value::of(width), Value::of(height)];
+		trace.clear();
+		target.make_current();
+		gl.default_framebuffer().make_current();
+		let viewports = trace.named("Viewport");
+		assert_eq!(viewports[0].args, viewport(8, 6));
+		assert_eq!(viewports[1].args, viewport(640, 480));
+	}
+
+	// answers 0 to framebuffer checks, as drivers do when the check fails
+	struct FailingCheck(Recorder);
+
+	impl Backend for FailingCheck {
+		fn call(&mut self, call : &Call) -> Value {
+			let value = self.0.call(call);
+			if call.name.starts_with("Check") { Value::Int(0) } else { value }
+		}
+	}
+
+	#[test]
+	fn unknown_framebuffer_status_is_an_error() {
+		let _gl = GLWrap::with_backend(FailingCheck(Recorder::new()));
+
+		let color = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		let framebuffer = FrameBufferBuilder::new().attach_color(&color).build();
+		assert!(matches!(framebuffer, Err(GLError::UnknownFrameBufferStatus(0))));
 	}
 }
@@ -0,0 +1,43 @@
+// -- INDIRECT COMMANDS
+// layouts are fixed by the OpenGL spec, they must not be reordered
+
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DrawArraysIndirectCommand {
+	pub count          : gl::types::GLuint,
+	pub instance_count : gl::types::GLuint,
+	pub first          : gl::types::GLuint,
+	pub base_instance  : gl::types::GLuint,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DrawElementsIndirectCommand {
+	pub count          : gl::types::GLuint,
+	pub instance_count : gl::types::GLuint,
+	pub first_index    : gl::types::GLuint,
+	pub base_vertex    : gl::types::GLint,
+	pub base_instance  : gl::types::GLuint,
+}
+
+// SAFETY: "repr(C)" over 32-bit integers only, without padding
+unsafe impl bytemuck::Zeroable for DrawArraysIndirectCommand {}
+unsafe impl bytemuck::Pod for DrawArraysIndirectCommand {}
+unsafe impl bytemuck::Zeroable for DrawElementsIndirectCommand {}
+unsafe impl bytemuck::Pod for DrawElementsIndirectCommand {}
+
+
+// not vertex data: the commands are read by the draw calls only
+pub trait IndirectCommand : bytemuck::Pod {
+	// whether the command reads from the index buffer of the program
+	const INDEXED : bool;
+}
+
+impl IndirectCommand for DrawArraysIndirectCommand {
+	const INDEXED : bool = false;
+}
+
+impl IndirectCommand for DrawElementsIndirectCommand {
+	const INDEXED : bool = true;
+}
@@ -0,0 +1,460 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::*;
+use crate::preprocess::*;
+
+
+
+// -- TYPED SHADER INTERFACE
+// names of attributes, uniforms & uniform blocks carrying the type of their data, so that
+// "Program::bind_typed", "Program::uniform_typed"... only accept matching data
+//
+// "generate" writes one struct of such names per program, from a build script:
+//
+//   // build.rs
+//   fn main() {
+//       migl::interface::generate("resources/shaders", &format!("{}/shaders.rs", std::env::var("OUT_DIR").unwrap())).unwrap();
+//   }
+//
+//   // main.rs
+//   include!(concat!(env!("OUT_DIR"), "/shaders.rs"));
+//   program.uniform_typed(JUG.model_view)?.pass(&model_view)?;
+
+
+pub struct AttributeName<T : ?Sized> {
+	pub name : &'static str,
+	phantom  : std::marker::PhantomData<*const T>,
+}
+
+pub struct UniformName<T : ?Sized> {
+	pub name : &'static str,
+	phantom  : std::marker::PhantomData<*const T>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextureName {
+	pub name : &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlockName {
+	pub name : &'static str,
+}
+
+impl<T : ?Sized> AttributeName<T> {
+	pub const fn new(name : &'static str) -> Self { Self { name, phantom : std::marker::PhantomData } }
+}
+
+impl<T : ?Sized> UniformName<T> {
+	pub const fn new(name : &'static str) -> Self { Self { name, phantom : std::marker::PhantomData } }
+}
+
+impl TextureName {
+	pub const fn new(name : &'static str) -> Self { Self { name } }
+}
+
+impl BlockName {
+	pub const fn new(name : &'static str) -> Self { Self { name } }
+}
+
+// derives would require T : Copy
+impl<T : ?Sized> Clone for AttributeName<T> { fn clone(&self) -> Self { *self } }
+impl<T : ?Sized> Copy  for AttributeName<T> {}
+impl<T : ?Sized> Clone for UniformName<T>   { fn clone(&self) -> Self { *self } }
+impl<T : ?Sized> Copy  for UniformName<T>   {}
+
+
+
+// -- REFLECTION
+// a declaration-level reading of GLSL, enough for the interface of a program:
+// comments & preprocessor lines are ignored, so declarations in every '#ifdef' branch are kept
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+	pub glsl_type : String,
+	pub name      : String,
+	// "N" for "name[N]"
+	pub array_len : Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interface {
+	pub attributes : Vec<Declaration>,
+	pub uniforms   : Vec<Declaration>,
+	pub blocks     : Vec<String>,
+}
+
+impl Interface {
+	// inputs are attributes only in a vertex shader
+	pub fn parse(source : &str, is_vertex : bool) -> Self {
+		let mut to_return = Self::default();
+		let source = strip_comments(source)
+			.lines()
+			.filter(|line| !line.trim_start().starts_with('#'))
+			.collect::<Vec<&str>>()
+			.join("\n");
+
+		let mut statement = String::new();
+		let mut chars = source.chars();
+		while let Some(c) = chars.next() {
+			match c {
+				';' => {
+					to_return.declaration(&statement, is_vertex);
+					statement.clear();
+				},
+				'{' => {
+					let words = words(&statement);
+					if words.first().map(String::as_str) == Some("uniform") && words.len() == 2 {
+						to_return.add_block(&words[1]);
+					}
+					// skip the block or function body, and what closes it
+					skip_braces(&mut chars);
+					statement.clear();
+					if words.first().map(String::as_str) == Some("uniform") {
+						for c in chars.by_ref() {
+							if c == ';' { break; }
+						}
+					}
+				},
+				_   => statement.push(c),
+			}
+		}
+		to_return
+	}
+
+	// declarations of "other" that aren't in "self" yet, e.g. uniforms of another stage
+	pub fn merge(&mut self, other : Interface) {
+		for attribute in other.attributes {
+			if !self.attributes.iter().any(|known| known.name == attribute.name) {
+				self.attributes.push(attribute);
+			}
+		}
+		for uniform in other.uniforms {
+			if !self.uniforms.iter().any(|known| known.name == uniform.name) {
+				self.uniforms.push(uniform);
+			}
+		}
+		for block in other.blocks {
+			self.add_block(&block);
+		}
+	}
+
+	fn add_block(&mut self, block : &str) {
+		if !self.blocks.iter().any(|known| known == block) {
+			self.blocks.push(block.to_string());
+		}
+	}
+
+	fn declaration(&mut self, statement : &str, is_vertex : bool) {
+		let words = words(statement);
+		let mut words = words.iter().map(String::as_str).peekable();
+
+		let mut storage = None;
+		while let Some(&word) = words.peek() {
+			match word {
+				"uniform" | "in" | "attribute" | "out" | "const" | "buffer" | "shared" => storage = Some(word),
+				"flat" | "smooth" | "noperspective" | "centroid" | "sample" | "invariant"
+				| "highp" | "mediump" | "lowp" | "precise" => {},
+				_ => break,
+			}
+			words.next();
+		}
+		let list = match storage {
+			Some("uniform")                             => &mut self.uniforms,
+			Some("in") | Some("attribute") if is_vertex => &mut self.attributes,
+			_                                           => return,
+		};
+
+		// e.g. "layout (points) in" has no type
+		let glsl_type = match words.next() {
+			Some(glsl_type) => glsl_type.to_string(),
+			None            => return,
+		};
+		let declarators : String = words.collect::<Vec<&str>>().join(" ");
+		for declarator in declarators.split(',') {
+			// "= initializer"
+			let declarator = declarator.split('=').next().unwrap_or("").trim();
+			let (name, array_len) = match declarator.split_once('[') {
+				Some((name, rest)) => (name.trim(), Some(rest.trim_end_matches(']').trim().to_string())),
+				None               => (declarator, None),
+			};
+			if !name.is_empty() && !list.iter().any(|known| known.name == name) {
+				list.push(Declaration { glsl_type : glsl_type.clone(), name : name.to_string(), array_len });
+			}
+		}
+	}
+}
+
+
+// words of a statement, without the 'layout(...)' qualifier
+fn words(statement : &str) -> Vec<String> {
+	let text = match statement.trim_start().strip_prefix("layout") {
+		Some(rest) => rest.split_once(')').map_or("", |(_, rest)| rest),
+		None       => statement,
+	};
+
+	// "name [4]" -> "name[4]", so that each declarator is a single word
+	text.replace(" [", "[")
+		.split_whitespace()
+		.map(str::to_string)
+		.collect()
+}
+
+fn strip_comments(source : &str) -> String {
+	let mut to_return = String::with_capacity(source.len());
+	let mut chars = source.chars().peekable();
+	while let Some(c) = chars.next() {
+		match (c, chars.peek()) {
+			('/', Some('/')) => {
+				for c in chars.by_ref() {
+					if c == '\n' { to_return.push('\n'); break; }
+				}
+			},
+			('/', Some('*')) => {
+				chars.next();
+				let mut previous = ' ';
+				for c in chars.by_ref() {
+					if previous == '*' && c == '/' { break; }
+					previous = c;
+				}
+				to_return.push(' ');
+			},
+			_ => to_return.push(c),
+		}
+	}
+	to_return
+}
+
+// consumes up to the '}' closing an already consumed '{'
+fn skip_braces(chars : &mut std::str::Chars) {
+	let mut depth = 1;
+	for c in chars.by_ref() {
+		match c {
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 { return; }
+			},
+			_ => {},
+		}
+	}
+}
+
+
+
+// -- CODE GENERATION
+
+
+// Rust type fed to an attribute, None if there is none
+fn attribute_type(glsl_type : &str) -> Option<&'static str> {
+	match glsl_type {
+		"float" => Some("f32"),
+		"int"   => Some("i32"),
+		"uint"  => Some("u32"),
+		"vec2"  => Some("::migl::math3d::V2"),
+		"vec3"  => Some("::migl::math3d::V3"),
+		"vec4"  => Some("::migl::math3d::V4"),
+		_       => None,
+	}
+}
+
+fn uniform_type(glsl_type : &str) -> Option<&'static str> {
+	match glsl_type {
+		"mat2" => Some("::migl::math3d::M22"),
+		"mat3" => Some("::migl::math3d::M33"),
+		"mat4" => Some("::migl::math3d::M44"),
+		"mat2x3" => Some("[[f32; 2]; 3]"), "mat2x4" => Some("[[f32; 2]; 4]"),
+		"mat3x2" => Some("[[f32; 3]; 2]"), "mat3x4" => Some("[[f32; 3]; 4]"),
+		"mat4x2" => Some("[[f32; 4]; 2]"), "mat4x3" => Some("[[f32; 4]; 3]"),
+		"dmat2"  => Some("[[f64; 2]; 2]"), "dmat3"  => Some("[[f64; 3]; 3]"), "dmat4" => Some("[[f64; 4]; 4]"),
+		"bool"   => Some("bool"),
+		"bvec2"  => Some("[bool; 2]"), "bvec3" => Some("[bool; 3]"), "bvec4" => Some("[bool; 4]"),
+		"ivec2"  => Some("[i32; 2]"),  "ivec3" => Some("[i32; 3]"),  "ivec4" => Some("[i32; 4]"),
+		"uvec2"  => Some("[u32; 2]"),  "uvec3" => Some("[u32; 3]"),  "uvec4" => Some("[u32; 4]"),
+		"double" => Some("[f64; 1]"),
+		"dvec2"  => Some("[f64; 2]"),  "dvec3" => Some("[f64; 3]"),  "dvec4" => Some("[f64; 4]"),
+		other    => attribute_type(other),
+	}
+}
+
+// the program made of the stages in directory "program_dir", e.g. "resources/shaders/jug"
+// None if the directory holds no vertex and fragment shader
+pub fn generate_program(program_dir : &Path, preprocessor : &Preprocessor) -> Result<Option<String>, GLError> {
+	let stage_path = |stage : &str| program_dir.join(format!("{}.glsl", stage)).to_string_lossy().replace('\\', "/");
+	let vert = stage_path("vert");
+	let frag = stage_path("frag");
+	let geom = stage_path("geom");
+	if !Path::new(&vert).is_file() || !Path::new(&frag).is_file() {
+		return Ok(None);
+	}
+	let geom = Some(geom).filter(|geom| Path::new(geom).is_file());
+
+	let mut interface = Interface::parse(&preprocessor.process_file(&vert)?.source, true);
+	for path in std::iter::once(&frag).chain(geom.iter()) {
+		interface.merge(Interface::parse(&preprocessor.process_file(path)?.source, false));
+	}
+
+	let program_name = program_dir
+		.file_name()
+		.map(|name| name.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	let struct_name = format!("{}Shader", camel_case(&program_name));
+
+	let mut fields = Vec::new();
+	for attribute in &interface.attributes {
+		match (attribute_type(&attribute.glsl_type), &attribute.array_len) {
+			(Some(rust_type), None) => fields.push((attribute.name.as_str(), format!("::migl::interface::AttributeName<{}>", rust_type), "AttributeName")),
+			_                       => fields.push((attribute.name.as_str(), String::new(), "")),
+		}
+	}
+	for uniform in &interface.uniforms {
+		if uniform.glsl_type.starts_with("sampler") {
+			fields.push((uniform.name.as_str(), "::migl::interface::TextureName".to_string(), "TextureName"));
+			continue;
+		}
+		match (uniform_type(&uniform.glsl_type), &uniform.array_len) {
+			(Some(rust_type), None)    => fields.push((uniform.name.as_str(), format!("::migl::interface::UniformName<{}>", rust_type), "UniformName")),
+			(Some(rust_type), Some(_)) => fields.push((uniform.name.as_str(), format!("::migl::interface::UniformName<[{}]>", rust_type), "UniformName")),
+			(None, _)                  => fields.push((uniform.name.as_str(), String::new(), "")),
+		}
+	}
+	for block in &interface.blocks {
+		fields.push((block.as_str(), "::migl::interface::BlockName".to_string(), "BlockName"));
+	}
+
+	let mut code = String::new();
+	writeln!(code, "// interface of the program in \"{}\"", program_dir.to_string_lossy()).unwrap();
+	writeln!(code, "#[allow(dead_code)]").unwrap();
+	writeln!(code, "pub struct {} {{", struct_name).unwrap();
+	for (name, rust_type, _) in &fields {
+		if rust_type.is_empty() {
+			writeln!(code, "\t// {} : no Rust type for this GLSL type", name).unwrap();
+		}
+		else {
+			writeln!(code, "\tpub {} : {},", field_name(name), rust_type).unwrap();
+		}
+	}
+	writeln!(code, "}}\n").unwrap();
+
+	writeln!(code, "#[allow(dead_code)]").unwrap();
+	writeln!(code, "impl {} {{", struct_name).unwrap();
+	writeln!(code, "\tpub const VERT : &'static str = {:?};", vert).unwrap();
+	writeln!(code, "\tpub const FRAG : &'static str = {:?};", frag).unwrap();
+	writeln!(code, "\tpub const GEOM : Option<&'static str> = {:?};", geom).unwrap();
+	writeln!(code, "}}\n").unwrap();
+
+	writeln!(code, "#[allow(dead_code)]").unwrap();
+	writeln!(code, "pub const {} : {} = {} {{", program_name.to_uppercase(), struct_name, struct_name).unwrap();
+	for (name, _, constructor) in fields.iter().filter(|(_, rust_type, _)| !rust_type.is_empty()) {
+		writeln!(code, "\t{} : ::migl::interface::{}::new({:?}),", field_name(name), constructor, name).unwrap();
+	}
+	writeln!(code, "}};").unwrap();
+
+	Ok(Some(code))
+}
+
+// writes the interface of every program directory in "shader_dir" to "out_file"
+// to be called from a build script, which it asks to rerun when the shaders change
+pub fn generate(shader_dir : &str, out_file : &str) -> Result<(), GLError> {
+	let mut program_dirs : Vec<_> = std::fs::read_dir(shader_dir)?
+		.map(|entry| entry.map(|entry| entry.path()))
+		.collect::<Result<_, _>>()?;
+	program_dirs.retain(|path| path.is_dir());
+	program_dirs.sort();
+
+	let preprocessor = Preprocessor::new();
+	let mut code = String::from("// generated by migl::interface::generate, do not edit\n\n");
+	for program_dir in program_dirs {
+		if let Some(program) = generate_program(&program_dir, &preprocessor)? {
+			code.push_str(&program);
+			code.push('\n');
+		}
+	}
+
+	std::fs::write(out_file, code)?;
+	println!("cargo:rerun-if-changed={}", shader_dir);
+	Ok(())
+}
+
+
+// "point_light" -> "PointLight"
+fn camel_case(name : &str) -> String {
+	name.split(|c : char| !c.is_ascii_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(|word| {
+			let mut chars = word.chars();
+			chars.next().map(|first| first.to_ascii_uppercase()).into_iter().chain(chars).collect::<String>()
+		})
+		.collect()
+}
+
+// GLSL names that are Rust keywords
+fn field_name(name : &str) -> String {
+	const KEYWORDS : &[&str] = &[
+		"as", "box", "break", "const", "continue", "crate", "else", "enum", "extern", "fn", "for", "if", "impl",
+		"in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "trait",
+		"type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "final", "override", "yield",
+	];
+	if KEYWORDS.contains(&name) { format!("r#{}", name) } else { name.to_string() }
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_declarations() {
+		let source = "
+			#version 330 core
+			layout (location = 0) in vec3 position;
+			in vec2 tex_coords, /* second */ extra;
+			flat out vec4 f_color;
+			uniform mat4 bones[16];
+			uniform float strength = 1.0; // default
+			layout (std140) uniform Lights {
+				vec4 colors[4];
+			} lights;
+			void main() { float unused; }
+		";
+		let interface = Interface::parse(source, true);
+		let names : Vec<&str> = interface.attributes.iter().map(|declaration| declaration.name.as_str()).collect();
+		assert_eq!(names, ["position", "tex_coords", "extra"]);
+		assert_eq!(interface.uniforms, [
+			Declaration { glsl_type : "mat4".into(),  name : "bones".into(),    array_len : Some("16".into()) },
+			Declaration { glsl_type : "float".into(), name : "strength".into(), array_len : None },
+		]);
+		assert_eq!(interface.blocks, ["Lights"]);
+	}
+
+	#[test]
+	fn fragment_inputs_are_not_attributes() {
+		let interface = Interface::parse("in vec3 f_normal; uniform sampler2D diffuse;", false);
+		assert!(interface.attributes.is_empty());
+		assert_eq!(interface.uniforms[0].glsl_type, "sampler2D");
+	}
+
+	#[test]
+	fn generates_repo_programs() {
+		let code = generate_program(Path::new("resources/shaders/jug"), &Preprocessor::new()).unwrap().unwrap();
+		assert!(code.contains("pub struct JugShader {"));
+		assert!(code.contains("\tpub position : ::migl::interface::AttributeName<::migl::math3d::V3>,\n"));
+		assert!(code.contains("\tpub model_view : ::migl::interface::UniformName<::migl::math3d::M44>,\n"));
+		// through '#include "../common/lighting.glsl"'
+		assert!(code.contains("\tpub camera_pos : ::migl::interface::UniformName<::migl::math3d::V3>,\n"));
+		assert!(code.contains("\tpub diffuse_texture : ::migl::interface::TextureName,\n"));
+		assert!(code.contains("pub const JUG : JugShader = JugShader {"));
+
+		let rays = generate_program(Path::new("resources/shaders/rays"), &Preprocessor::new()).unwrap().unwrap();
+		assert!(rays.contains("pub const GEOM : Option<&'static str> = Some(\"resources/shaders/rays/geom.glsl\");"));
+		assert!(rays.contains("\tpub ray_dir : "));
+
+		assert!(generate_program(Path::new("resources/shaders/common"), &Preprocessor::new()).unwrap().is_none());
+	}
+}
@@ -1,18 +1,40 @@
 pub mod shader;
+pub mod preprocess;
+pub mod diagnostics;
+pub mod reload;
+pub mod cache;
+pub mod interface;
 pub mod texture;
 pub mod log;
 #[cfg(feature = "utils")]
 pub mod utils;
+#[cfg(feature = "software")]
+pub mod software;
 pub mod attributes;
 pub mod program;
+pub mod pipeline;
 pub mod uniform;
 pub mod frame;
+pub mod renderbuffer;
 pub mod buffer;
+pub mod indirect;
+pub mod query;
+pub mod profiler;
+pub mod sync;
+pub mod mapping;
+pub mod dsa;
+pub mod capabilities;
+pub mod debug;
+pub mod trace;
 pub mod error;
 pub mod math3d;
 
 extern crate gl;
 
+use std::sync::Arc;
+
+use crate::capabilities::*;
+use crate::debug::*;
 use crate::error::*;
 use frame::{FrameBuffer, FrameBufferId};
 
@@ -21,6 +43,8 @@ use frame::{FrameBuffer, FrameBufferId};
 pub struct GLWrap {
 	next_free_uniform_binding_pt : UniformBindingPoint,
 	default_framebuffer : FrameBuffer,
+	capabilities : Arc<Capabilities>,
+	debug_output : Option<DebugOutput>,
 }
 
 
@@ -37,9 +61,26 @@ impl GLWrap {
 	where F : FnMut(&'static str) -> *const std::os::raw::c_void
 	{
 		gl::load_with(loadfn);
+		Self::init()
+	}
+
+	// GL calls of this thread go to "backend" rather than to a driver, e.g. a "trace::Recorder" in tests
+	// the entry points are replaced for the whole process
+	pub fn with_backend<B>(backend : B) -> Self
+	where B : trace::Backend + 'static
+	{
+		trace::install(Box::new(backend));
+		trace::load_stubs();
+		Self::init()
+	}
+
+	fn init() -> Self {
+		let capabilities = Arc::new(Capabilities::query());
+		capabilities::set_current(capabilities.clone());
+		dsa::set_enabled(dsa::is_supported());
 		Self::enable_depth();
 		let next_free_uniform_binding_pt = UniformBindingPoint(0);
-		Self {next_free_uniform_binding_pt, default_framebuffer: FrameBuffer::default() }
+		Self {next_free_uniform_binding_pt, default_framebuffer: FrameBuffer::default(), capabilities, debug_output: None }
 	}
 
 	pub fn set_clear_color(&self, r : f32, g : f32, b : f32, a : f32)
@@ -59,8 +100,11 @@ impl GLWrap {
 		unsafe {gl::PointSize(size);}
 	}
 
+	// the viewport of the default framebuffer, e.g. the window's size : applied now, and again each time
+	// the default framebuffer is made current after an offscreen pass, see "FrameBuffer::make_current"
 	pub fn set_viewport(&self, x : i32, y : i32, w : i32, h : i32)
 	{
+		self.default_framebuffer.set_viewport(x, y, w, h);
 		unsafe {
 			gl::Viewport(x, y, w, h);
 		}
@@ -71,6 +115,17 @@ impl GLWrap {
 		unsafe {gl::Enable(gl::PROGRAM_POINT_SIZE); }
 	}
 
+	// stops primitives from reaching the rasterizer, e.g. when only capturing transform feedback
+	pub fn enable_rasterizer_discard(&self) 
+	{
+		unsafe {gl::Enable(gl::RASTERIZER_DISCARD); }
+	}
+
+	pub fn disable_rasterizer_discard(&self) 
+	{
+		unsafe {gl::Disable(gl::RASTERIZER_DISCARD); }
+	}
+
 	pub fn clear(&self)
 	{
 		unsafe {
@@ -87,6 +142,64 @@ impl GLWrap {
 
 	pub fn default_framebuffer(&self) -> &FrameBuffer { &self.default_framebuffer }
 
+	// version, limits & extensions of the context, queried once
+	pub fn capabilities(&self) -> &Capabilities { &self.capabilities }
+
+	// whether objects are edited through Direct State Access, see "dsa"
+	pub fn uses_dsa(&self) -> bool { dsa::is_enabled() }
+
+	// e.g. to compare both paths, best called before creating any object
+	pub fn set_dsa(&mut self, enabled : bool) -> Result<(), GLError> {
+		if enabled && !dsa::is_supported() {
+			return Err(GLError::Unsupported(Feature::DirectStateAccess));
+		}
+		dsa::set_enabled(enabled);
+		Ok(())
+	}
+
+	// "callback" receives the driver's messages of at least "min_severity", until disabled
+	// it runs inside the GL call that triggered the message, and must not call GL itself
+	pub fn enable_debug_output<F>(&mut self, min_severity : DebugSeverity, callback : F) -> Result<(), GLError>
+	where F : FnMut(&DebugMessage) + 'static
+	{
+		self.capabilities.require(Feature::DebugOutput)?;
+		// the previous callback is unregistered first
+		self.debug_output = None;
+		self.debug_output = Some(DebugOutput::enable(min_severity, Box::new(callback)));
+		Ok(())
+	}
+
+	// forwards the driver's messages to the "log" crate, high severity as errors down to notifications as debug
+	#[cfg(feature = "log")]
+	pub fn log_debug_output(&mut self, min_severity : DebugSeverity) -> Result<(), GLError> {
+		self.enable_debug_output(min_severity, debug::log_message)
+	}
+
+	pub fn disable_debug_output(&mut self) {
+		self.debug_output = None;
+	}
+
+	// groups the following calls under "name" in debugging tools, until "pop_debug_group"
+	pub fn push_debug_group(&self, name : &str) {
+		debug::push_group(name);
+	}
+
+	pub fn pop_debug_group(&self) {
+		debug::pop_group();
+	}
+
+	// scoped "push_debug_group", popped when the group is dropped
+	pub fn debug_group(&self, name : &str) -> DebugGroup {
+		DebugGroup::push(name)
+	}
+
+	// wrapped calls then check glGetError and fail with "GLError::Driver", in debug builds only
+	pub fn set_error_checking(&mut self, enabled : bool) {
+		error::set_checking_errors(enabled);
+	}
+
+	pub fn checks_errors(&self) -> bool { error::is_checking_errors() }
+
 	pub fn enable_depth() {
 		unsafe {
 			gl::DepthFunc(gl::LESS);
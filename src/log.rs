@@ -10,53 +10,56 @@ pub enum LogKind {
 }
 
 
-pub fn get_log(id : GLuint, log_kind : LogKind) -> Option<String> {
+// whether compilation / linking succeeded, and the info log
+// the log is read even on success, as it may hold warnings
+pub fn get_status_and_log(id : GLuint, log_kind : LogKind) -> (bool, String) {
 	let mut success: gl::types::GLint = 1;
+	let mut info_log_length: gl::types::GLint = 0;
 	unsafe {
 		match &log_kind {
-			LogKind::CompilerLog => gl::GetShaderiv  (id, gl::COMPILE_STATUS, &mut success),
-			LogKind::LinkLog     => gl::GetProgramiv (id, gl::LINK_STATUS,    &mut success),
+			LogKind::CompilerLog => {
+				gl::GetShaderiv  (id, gl::COMPILE_STATUS,  &mut success);
+				gl::GetShaderiv  (id, gl::INFO_LOG_LENGTH, &mut info_log_length);
+			},
+			LogKind::LinkLog     => {
+				gl::GetProgramiv (id, gl::LINK_STATUS,     &mut success);
+				gl::GetProgramiv (id, gl::INFO_LOG_LENGTH, &mut info_log_length);
+			},
 		}
 	}
 
+	if info_log_length <= 0 {
+		return (success != 0, String::new());
+	}
 
-	if success == 0 {
-		let mut info_log_length: gl::types::GLint = -1;
-		unsafe {
-			match &log_kind {
-				LogKind::CompilerLog => gl::GetShaderiv  (id, gl::INFO_LOG_LENGTH, &mut info_log_length),
-				LogKind::LinkLog     => gl::GetProgramiv (id, gl::INFO_LOG_LENGTH, &mut info_log_length),
-			}
-		}
+	// -- CREATE BUFFER OF CORRECT SIZE (including null terminator)
+	let mut buffer: Vec<u8> = vec![0; info_log_length as usize];
+	let mut written : gl::types::GLsizei = 0;
+
+	unsafe {
+		match &log_kind {
+			LogKind::CompilerLog => gl::GetShaderInfoLog(
+			    id,
+			    info_log_length,
+			    &mut written,
+			    buffer.as_mut_ptr() as *mut gl::types::GLchar
+			),
+			LogKind::LinkLog => gl::GetProgramInfoLog(
+			    id,
+			    info_log_length,
+			    &mut written,
+			    buffer.as_mut_ptr() as *mut gl::types::GLchar
+			),
 
-		// -- CREATE BUFFER OF CORRECT SIZE
-		// allocate buffer of correct size
-		let mut buffer: Vec<u8> = Vec::with_capacity(info_log_length as usize + 1);
-		// fill it with info_log_length spaces
-		for _ in 1 .. info_log_length {buffer.push(b' ')}
-		// convert buffer to CString
-		let error: std::ffi::CString = unsafe { std::ffi::CString::from_vec_unchecked(buffer) };
-
-		unsafe {
-			match &log_kind {
-				LogKind::CompilerLog => gl::GetShaderInfoLog(
-				    id,
-				    info_log_length,
-				    std::ptr::null_mut(),
-				    error.as_ptr() as *mut gl::types::GLchar
-				),
-				LogKind::LinkLog => gl::GetProgramInfoLog(
-				    id,
-				    info_log_length,
-				    std::ptr::null_mut(),
-				    error.as_ptr() as *mut gl::types::GLchar
-				),
-
-			}
 		}
-		Some(error.to_string_lossy().into_owned())
-	}
-	else {
-		None
 	}
+	buffer.truncate(written.max(0) as usize);
+	(success != 0, String::from_utf8_lossy(&buffer).into_owned())
+}
+
+
+// the info log if compilation / linking failed
+pub fn get_log(id : GLuint, log_kind : LogKind) -> Option<String> {
+	let (success, log) = get_status_and_log(id, log_kind);
+	if success { None } else { Some(log) }
 }
@@ -0,0 +1,351 @@
+use std::ops::{Deref, DerefMut, Range};
+use std::time::Duration;
+
+use gl::types::*;
+
+use crate::attributes::*;
+use crate::buffer::*;
+use crate::capabilities::{self, Feature};
+use crate::dsa;
+use crate::error::*;
+use crate::sync::Fence;
+
+
+
+// -- MAPPING
+
+// flushes & persistent mappings are not handled here, hence the private bits
+// see "PersistentBuffer" for the latter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapAccess(GLbitfield);
+
+impl MapAccess {
+	pub const READ       : Self = Self(gl::MAP_READ_BIT);
+	pub const WRITE      : Self = Self(gl::MAP_WRITE_BIT);
+	pub const READ_WRITE : Self = Self(gl::MAP_READ_BIT | gl::MAP_WRITE_BIT);
+
+	// previous content of the range may be discarded by the driver
+	pub fn invalidate(self) -> Self {
+		Self(self.0 | gl::MAP_INVALIDATE_RANGE_BIT)
+	}
+
+	// no implicit synchronization with pending GPU commands, see "sync::Fence"
+	pub fn unsynchronized(self) -> Self {
+		Self(self.0 | gl::MAP_UNSYNCHRONIZED_BIT)
+	}
+
+	#[inline]
+	fn contains(self, bits : GLbitfield) -> bool { self.0 & bits == bits }
+}
+
+
+// a mapped range of a buffer, unmapped when dropped
+// "MappedSlice" for ranges mapped for reading, "MappedSliceMut" for ranges mapped for writing
+struct Mapping<A> {
+	id      : BufferId,
+	kind    : BufferKind,
+	ptr     : *mut A,
+	n_elems : usize,
+}
+
+impl<A : bytemuck::Pod> Mapping<A> {
+	#[track_caller]
+	fn new(buffer : &Buffer<A>, range : Range<usize>, access : MapAccess) -> Result<Self, GLError> {
+		let elem_size = std::mem::size_of::<A>();
+		let (id, kind) = (buffer.id(), buffer.kind());
+		let ptr = unsafe {
+			dsa::map_buffer_range(kind.cst(), id.0, range.start * elem_size, range.len() * elem_size, access.0)
+		};
+		if ptr.is_null() {
+			return Err(GLError::CouldNotMapBuffer);
+		}
+
+		// unmapped on error
+		let mapping = Self { id, kind, ptr : ptr.cast(), n_elems : range.len() };
+		check("Buffer::map_range")?;
+		Ok(mapping)
+	}
+}
+
+impl<A> Drop for Mapping<A> {
+	fn drop(&mut self) {
+		unsafe {
+			dsa::unmap_buffer(self.kind.cst(), self.id.0);
+		}
+	}
+}
+
+
+pub struct MappedSlice<'a, A> {
+	mapping  : Mapping<A>,
+	_phantom : std::marker::PhantomData<&'a mut Buffer<A>>,
+}
+
+impl<'a, A : bytemuck::Pod> MappedSlice<'a, A> {
+	// "access" must allow reading ; "range" must be within the elements of the buffer
+	#[track_caller]
+	pub(crate) fn new(buffer : &'a mut Buffer<A>, range : Range<usize>, access : MapAccess) -> Result<Self, GLError> {
+		if !access.contains(gl::MAP_READ_BIT) {
+			return Err(GLError::InvalidMapAccess);
+		}
+		if range.is_empty() || range.end > buffer.len() {
+			return Err(GLError::InvalidMapRange);
+		}
+		let mapping = Mapping::new(buffer, range, access)?;
+		Ok(Self { mapping, _phantom : std::marker::PhantomData })
+	}
+}
+
+impl<'a, A> Deref for MappedSlice<'a, A> {
+	type Target = [A];
+
+	fn deref(&self) -> &[A] {
+		unsafe { std::slice::from_raw_parts(self.mapping.ptr, self.mapping.n_elems) }
+	}
+}
+
+
+pub struct MappedSliceMut<'a, A> {
+	mapping  : Mapping<A>,
+	_phantom : std::marker::PhantomData<&'a mut Buffer<A>>,
+}
+
+impl<'a, A : bytemuck::Pod> MappedSliceMut<'a, A> {
+	// "access" must allow writing ; "range" may extend past the elements of the buffer, up to its capacity
+	// the mapped elements then become part of the buffer, as with "Buffer::replace_data"
+	#[track_caller]
+	pub(crate) fn new(buffer : &'a mut Buffer<A>, range : Range<usize>, access : MapAccess) -> Result<Self, GLError> {
+		if !access.contains(gl::MAP_WRITE_BIT) {
+			return Err(GLError::InvalidMapAccess);
+		}
+		if range.is_empty() || range.end > buffer.capacity() {
+			return Err(GLError::InvalidMapRange);
+		}
+		let end = range.end;
+		let mapping = Mapping::new(buffer, range, access)?;
+		buffer.set_len(buffer.len().max(end));
+		Ok(Self { mapping, _phantom : std::marker::PhantomData })
+	}
+}
+
+impl<'a, A> Deref for MappedSliceMut<'a, A> {
+	type Target = [A];
+
+	fn deref(&self) -> &[A] {
+		unsafe { std::slice::from_raw_parts(self.mapping.ptr, self.mapping.n_elems) }
+	}
+}
+
+impl<'a, A> DerefMut for MappedSliceMut<'a, A> {
+	fn deref_mut(&mut self) -> &mut [A] {
+		unsafe { std::slice::from_raw_parts_mut(self.mapping.ptr, self.mapping.n_elems) }
+	}
+}
+
+
+
+// -- PERSISTENT BUFFER
+// immutable storage mapped once for its whole lifetime
+// the CPU writes directly into GPU-visible memory; synchronization is left to the caller
+// the storage starts zeroed, and any bytes must make a valid "A", hence "Pod"
+
+#[derive(Debug)]
+pub struct PersistentBuffer<A> {
+	raw     : RawBuffer,
+	ptr     : *mut A,
+	n_elems : usize,
+}
+
+impl<A : bytemuck::Pod> PersistentBuffer<A> {
+	#[track_caller]
+	pub(crate) fn new(kind : BufferKind, n_elems : usize) -> Result<Self, GLError> {
+		capabilities::require(Feature::BufferStorage)?;
+		let size = n_elems.checked_mul(std::mem::size_of::<A>()).ok_or(GLError::InvalidMapRange)?;
+		if size == 0 {
+			return Err(GLError::InvalidMapRange);
+		}
+		let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+
+		let buffer_id = dsa::create_buffer();
+		if buffer_id == 0 {
+			return Err(GLError::CouldNotCreateBuffer);
+		}
+
+		let zeroes = vec![0u8; size];
+		let ptr = unsafe {
+			dsa::buffer_storage(kind.cst(), buffer_id, size, zeroes.as_ptr().cast(), flags);
+			dsa::map_buffer_range(kind.cst(), buffer_id, 0, size, flags)
+		};
+		if ptr.is_null() {
+			unsafe {gl::DeleteBuffers(1, &buffer_id);}
+			return Err(GLError::CouldNotMapBuffer);
+		}
+
+		// unmapped and deleted on error
+		let buffer = Self {
+			raw : RawBuffer { id : BufferId(buffer_id), kind, update : UpdateKind::Dynamic, access : AccessKind::Draw },
+			ptr : ptr.cast(),
+			n_elems,
+		};
+		check("PersistentBuffer::new")?;
+		Ok(buffer)
+	}
+
+	#[inline]
+	pub fn id(&self)   -> BufferId     { self.raw.id      }
+	#[inline]
+	pub fn kind(&self) -> BufferKind   { self.raw.kind    }
+	#[inline]
+	pub fn len(&self)  -> usize        { self.n_elems     }
+	#[inline]
+	pub fn is_empty(&self) -> bool     { self.n_elems == 0 }
+
+	// the GPU may be reading any part of the buffer: writes must be fenced by the caller
+	pub fn as_mut_slice(&mut self) -> &mut [A] {
+		unsafe { std::slice::from_raw_parts_mut(self.ptr, self.n_elems) }
+	}
+}
+
+impl<A : GPUData> PersistentBuffer<A> {
+	// "range" is in elements
+	pub fn view_range(&self, range : Range<usize>) -> Result<BufferView, GLError>
+	{
+		if range.start > range.end {
+			return Err(GLError::BufferViewOutOfRange);
+		}
+		let stride = std::mem::size_of::<A>();
+		let offset = range.start.checked_mul(stride).ok_or(GLError::BufferViewOutOfRange)?;
+		BufferView::new(
+			self.raw.id,
+			self.n_elems * stride,
+			range.len(),
+			stride,
+			offset,
+			A::INFO,
+		)
+	}
+}
+
+impl<A> Drop for PersistentBuffer<A> {
+	fn drop(&mut self) {
+		unsafe {
+			dsa::unmap_buffer(self.raw.kind.cst(), self.raw.id.0);
+			gl::DeleteBuffers(1, &self.raw.id.0);
+		}
+	}
+}
+
+
+
+// -- STREAM RING
+// per-frame streaming: the persistent buffer is split into "n_regions" regions used in turn
+// a region is only handed out again once the GPU is done with the commands issued while it was current
+
+#[derive(Debug)]
+pub struct StreamRing<A> {
+	buffer     : PersistentBuffer<A>,
+	region_len : usize,
+	fences     : Vec<Option<Fence>>,
+	current    : usize,
+}
+
+pub struct StreamRegion<'a, A> {
+	pub data   : &'a mut [A],
+	// range of the region in the whole buffer, in elements
+	pub range  : Range<usize>,
+}
+
+impl<A : bytemuck::Pod> StreamRing<A> {
+	// 3 regions is enough for triple buffering
+	#[track_caller]
+	pub fn new(kind : BufferKind, region_len : usize, n_regions : usize) -> Result<Self, GLError> {
+		let n_elems = region_len.checked_mul(n_regions).ok_or(GLError::InvalidMapRange)?;
+		// also rejects 0 regions
+		let buffer = PersistentBuffer::new(kind, n_elems)?;
+		let fences = (0 .. n_regions).map(|_| None).collect();
+		Ok(Self { buffer, region_len, fences, current : n_regions - 1 })
+	}
+
+	#[inline]
+	pub fn buffer(&self) -> &PersistentBuffer<A> { &self.buffer }
+
+	// moves to the next region, waiting for the GPU to release it if needed
+	#[track_caller]
+	pub fn next_region(&mut self, timeout : Duration) -> Result<StreamRegion<'_, A>, GLError> {
+		let next = (self.current + 1) % self.fences.len();
+		if let Some(fence) = &self.fences[next] {
+			fence.wait_signaled(timeout)?;
+		}
+		self.fences[next] = None;
+		self.current = next;
+
+		let range = next * self.region_len .. (next + 1) * self.region_len;
+		let data  = &mut self.buffer.as_mut_slice()[range.clone()];
+		Ok(StreamRegion { data, range })
+	}
+
+	// to be called once every command reading the current region has been issued
+	#[track_caller]
+	pub fn release_region(&mut self) -> Result<(), GLError> {
+		self.fences[self.current] = Some(Fence::new()?);
+		Ok(())
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::trace::*;
+
+	#[test]
+	fn guards_follow_the_map_access() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let mut buffer = BufferBld::array().allocate::<u32>(8).unwrap();
+		buffer.pass_data(&[1, 2, 3]).unwrap();
+
+		assert!(matches!(buffer.map_range(0 .. 2, MapAccess::WRITE), Err(GLError::InvalidMapAccess)));
+		assert!(matches!(buffer.map_range_mut(0 .. 2, MapAccess::READ), Err(GLError::InvalidMapAccess)));
+		// past "len" for reading, past the capacity for writing
+		assert!(matches!(buffer.map_range(2 .. 4, MapAccess::READ), Err(GLError::InvalidMapRange)));
+		assert!(matches!(buffer.map_range_mut(6 .. 9, MapAccess::WRITE), Err(GLError::InvalidMapRange)));
+		assert!(trace.named("MapBufferRange").is_empty());
+
+		assert_eq!(buffer.map_range(0 .. 3, MapAccess::READ).unwrap().len(), 3);
+		let mut mapped = buffer.map_range_mut(2 .. 6, MapAccess::WRITE.invalidate()).unwrap();
+		mapped.copy_from_slice(&[4, 5, 6, 7]);
+		drop(mapped);
+		assert_eq!(buffer.len(), 6);
+		assert_eq!(trace.named("MapBufferRange")[1].args[1 ..], [
+			Value::of(8isize),
+			Value::of(16isize),
+			Value::of(gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT),
+		]);
+		assert_eq!(trace.named("UnmapBuffer").len(), 2);
+	}
+
+	#[test]
+	fn persistent_storage_starts_zeroed() {
+		let recorder = Recorder::new()
+			.with_integer(gl::MAJOR_VERSION, 4)
+			.with_integer(gl::MINOR_VERSION, 4);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let mut buffer = BufferBld::array().allocate_persistent::<u32>(4).unwrap();
+		assert_ne!(trace.named("BufferStorage")[0].args[2], Value::Ptr(0));
+		assert_eq!(buffer.as_mut_slice(), [0; 4]);
+
+		drop(buffer);
+		assert_eq!(trace.named("UnmapBuffer").len(), 1);
+		assert_eq!(trace.named("DeleteBuffers").len(), 1);
+	}
+}
@@ -29,6 +29,10 @@ impl Normed for f32 {
 #[derive(Debug, Clone, Copy)]
 pub struct Point<const N: usize> (pub [f32; N]);
 
+// SAFETY: "repr(C)" over floats only, without padding
+unsafe impl<const N: usize> bytemuck::Zeroable for Point<N> {}
+unsafe impl<const N: usize> bytemuck::Pod for Point<N> {}
+
 
 
 pub type V2 = Point<2>;
@@ -389,6 +393,10 @@ impl Mul<Quaternion> for Quaternion {
 #[derive(Debug, Clone, Copy)]
 pub struct Matrix<const N: usize> (pub [[f32; N]; N]);
 
+// SAFETY: "repr(C)" over floats only, without padding
+unsafe impl<const N: usize> bytemuck::Zeroable for Matrix<N> {}
+unsafe impl<const N: usize> bytemuck::Pod for Matrix<N> {}
+
 pub type M22 = Matrix<2>;
 pub type M33 = Matrix<3>;
 pub type M44 = Matrix<4>;
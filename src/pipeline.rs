@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::ops::BitOr;
+
+use gl::types::*;
+
+use crate::capabilities::{self, Feature};
+use crate::error::*;
+use crate::program::*;
+
+
+
+// -- PROGRAM PIPELINES
+// stages taken from several separable programs (see "ProgramBuilder::separable"), so that
+// one vertex program can be combined with several fragment programs without relinking
+// uniforms are still set on each program, through "Program::uniform"
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineStages(pub GLbitfield);
+
+impl PipelineStages {
+	pub const NONE     : Self = Self(0);
+	pub const VERTEX   : Self = Self(gl::VERTEX_SHADER_BIT);
+	pub const FRAGMENT : Self = Self(gl::FRAGMENT_SHADER_BIT);
+	pub const GEOMETRY : Self = Self(gl::GEOMETRY_SHADER_BIT);
+
+	pub fn contains(self, other : Self) -> bool {
+		self.0 & other.0 == other.0
+	}
+}
+
+impl BitOr for PipelineStages {
+	type Output = Self;
+	fn bitor(self, other : Self) -> Self { Self(self.0 | other.0) }
+}
+
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct PipelineId(pub GLuint);
+
+// borrows its programs: their textures and attribute bindings are used when drawing
+#[derive(Debug)]
+pub struct ProgramPipeline<'a> {
+	id     : PipelineId,
+	stages : Vec<(PipelineStages, &'a Program)>,
+}
+
+impl<'a> ProgramPipeline<'a> {
+	#[track_caller]
+	pub fn new() -> Result<Self, GLError> {
+		capabilities::require(Feature::SeparatePrograms)?;
+		let mut id = 0;
+		unsafe {gl::GenProgramPipelines(1, &mut id)};
+		if id == 0 {
+			return Err(GLError::CouldNotCreatePipeline);
+		}
+		let pipeline = Self { id : PipelineId(id), stages : Vec::new() };
+		check("ProgramPipeline::new")?;
+		Ok(pipeline)
+	}
+
+	#[inline]
+	pub fn id(&self) -> PipelineId { self.id }
+
+	// "stages" of "program" replace those of the programs previously used for them
+	// fails if the program isn't separable or lacks one of the stages
+	#[track_caller]
+	pub fn use_stages(&mut self, stages : PipelineStages, program : &'a Program) -> Result<(), GLError> {
+		if !program.is_separable() {
+			return Err(GLError::ProgramNotSeparable);
+		}
+		if !program.stages().contains(stages) {
+			return Err(GLError::MissingProgramStage);
+		}
+
+		unsafe {gl::UseProgramStages(self.id.0, stages.0, program.id.0)};
+		check("ProgramPipeline::use_stages")?;
+		for (used_stages, _) in self.stages.iter_mut() {
+			used_stages.0 &= !stages.0;
+		}
+		self.stages.retain(|(used_stages, _)| *used_stages != PipelineStages::NONE);
+		self.stages.push((stages, program));
+		Ok(())
+	}
+
+	// builder-style "use_stages"
+	#[track_caller]
+	pub fn with_stages(mut self, stages : PipelineStages, program : &'a Program) -> Result<Self, GLError> {
+		self.use_stages(stages, program)?;
+		Ok(self)
+	}
+
+	// the program providing the vertex stage, whose attributes & indices are used when drawing
+	pub fn vertex_program(&self) -> Option<&'a Program> {
+		self.stages
+			.iter()
+			.find(|(stages, _)| stages.contains(PipelineStages::VERTEX))
+			.map(|(_, program)| *program)
+	}
+
+	// whether the stages can run together, e.g. outputs of the vertex stage match the inputs of the fragment stage
+	#[track_caller]
+	pub fn validate(&self) -> Result<(), GLError> {
+		let mut status = 0;
+		let mut log_length = 0;
+		unsafe {
+			gl::ValidateProgramPipeline(self.id.0);
+			gl::GetProgramPipelineiv(self.id.0, gl::VALIDATE_STATUS, &mut status);
+			gl::GetProgramPipelineiv(self.id.0, gl::INFO_LOG_LENGTH, &mut log_length);
+		}
+		check("ProgramPipeline::validate")?;
+		if status != 0 {
+			return Ok(());
+		}
+
+		let mut log = vec![0u8; log_length.max(1) as usize];
+		let mut written = 0;
+		unsafe {gl::GetProgramPipelineInfoLog(self.id.0, log_length.max(1), &mut written, log.as_mut_ptr().cast())};
+		log.truncate(written.max(0) as usize);
+		Err(GLError::InvalidPipeline(String::from_utf8_lossy(&log).into_owned()))
+	}
+
+	// "draw" is called with the vertex program, e.g. "|program| program.draw_buffer(DrawMode::Tris)"
+	// the textures of every program are bound: fails if two programs set a texture unit to different textures
+	#[track_caller]
+	pub fn draw<R, F>(&self, draw : F) -> Result<R, GLError>
+	where F : FnOnce(&Program) -> Result<R, GLError>
+	{
+		let vertex_program = self.vertex_program().ok_or(GLError::MissingProgramStage)?;
+		self.check_texture_units()?;
+
+		// a current program would take precedence over the pipeline
+		unsafe {
+			gl::UseProgram(0);
+			gl::BindProgramPipeline(self.id.0);
+		}
+		check("ProgramPipeline::draw")?;
+		for (_, program) in self.stages.iter().filter(|(_, program)| program.id != vertex_program.id) {
+			program.bind_texture();
+		}
+		let result = draw(vertex_program);
+		unsafe {gl::BindProgramPipeline(0)};
+		result
+	}
+
+	// units are numbered from 0 in each program, see "Program::texture"
+	fn check_texture_units(&self) -> Result<(), GLError> {
+		let mut textures = HashMap::new();
+		for (_, program) in &self.stages {
+			for (unit, texture) in program.texture_units() {
+				if textures.insert(unit, texture).is_some_and(|other| other != texture) {
+					return Err(GLError::OverlappingTextureUnit(unit));
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Drop for ProgramPipeline<'_> {
+	fn drop(&mut self) {
+		unsafe {gl::DeleteProgramPipelines(1, &self.id.0)};
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::shader::*;
+	use crate::texture::*;
+	use crate::trace::*;
+
+	#[test]
+	fn programs_cannot_share_a_texture_unit() {
+		let recorder = Recorder::new().with_integer(gl::MAJOR_VERSION, 4).with_integer(gl::MINOR_VERSION, 1);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let program = || {
+			let vert = Shader::<Vertex>::from_str("void main() {}").unwrap();
+			let frag = Shader::<Fragment>::from_str("void main() {}").unwrap();
+			ProgramBuilder::new(vert, frag).attributes(&[]).separable().build().unwrap()
+		};
+		let (mut vertex_program, mut fragment_program) = (program(), program());
+		let texture = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		vertex_program.texture("heights", texture.clone()).unwrap();
+		fragment_program.texture("colors", texture).unwrap();
+
+		// unit 0 holds the same texture for both
+		let pipeline = ProgramPipeline::new().unwrap()
+			.with_stages(PipelineStages::VERTEX, &vertex_program).unwrap()
+			.with_stages(PipelineStages::FRAGMENT, &fragment_program).unwrap();
+		assert!(pipeline.draw(|_| Ok(())).is_ok());
+		drop(pipeline);
+
+		fragment_program.texture("colors", Texture::allocate(4, 4, TexFormat::Rgba).unwrap()).unwrap();
+		let pipeline = ProgramPipeline::new().unwrap()
+			.with_stages(PipelineStages::VERTEX, &vertex_program).unwrap()
+			.with_stages(PipelineStages::FRAGMENT, &fragment_program).unwrap();
+		trace.clear();
+		assert!(matches!(pipeline.draw(|_| Ok(())), Err(GLError::OverlappingTextureUnit(0))));
+		assert!(trace.named("BindProgramPipeline").is_empty());
+	}
+}
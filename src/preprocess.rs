@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::*;
+
+
+
+// -- PREPROCESSOR
+// resolves '#include "..."', injects '#version' & '#define's before the source reaches GL
+// every chunk of output is preceded by a '#line LINE FILE' directive, so that drivers report
+// the line in the original file, and the index of that file in "Preprocessed::files"
+// before GLSL 4.20, '#line N' numbers the next line N + 1: directives follow the output '#version'
+
+
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+	version       : Option<String>,
+	defines       : Vec<(String, String)>,
+	virtual_files : HashMap<PathBuf, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+	pub source : String,
+	// files making up the source, indexed by the source string number of '#line' directives
+	pub files  : Vec<String>,
+	// (index in "files", line in that file) of every line of "source", starting at 1
+	// None for lines injected by the preprocessor
+	pub lines  : Vec<Option<(usize, usize)>>,
+}
+
+impl Preprocessed {
+	// file and line in that file that line "line" (starting at 1) of the output comes from
+	pub fn original(&self, line : usize) -> Option<(&str, usize)> {
+		let (file, line) = (*self.lines.get(line.checked_sub(1)?)?)?;
+		Some((&self.files[file], line))
+	}
+
+	pub fn file_name(&self, file : usize) -> Option<&str> {
+		self.files.get(file).map(|file| file.as_str())
+	}
+
+	// text of line "line" of file number "file", as it appears in the output
+	pub fn line_text(&self, file : usize, line : usize) -> Option<&str> {
+		self.source
+			.lines()
+			.zip(self.lines.iter())
+			.find(|(_, origin)| **origin == Some((file, line)))
+			.map(|(text, _)| text)
+	}
+}
+
+
+impl Preprocessor {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	// e.g. "330 core", replaces the '#version' line of the source if any
+	pub fn version(mut self, version : &str) -> Self {
+		self.version = Some(version.to_string());
+		self
+	}
+
+	pub fn define(mut self, name : &str, value : &str) -> Self {
+		self.defines.push((name.to_string(), value.to_string()));
+		self
+	}
+
+	// defines every feature to 1
+	pub fn features(mut self, features : &[&str]) -> Self {
+		for feature in features {
+			self = self.define(feature, "1");
+		}
+		self
+	}
+
+	// an include of "path" resolves to "source" before looking on disk
+	pub fn virtual_file(mut self, path : &str, source : &str) -> Self {
+		self.virtual_files.insert(PathBuf::from(path), source.to_string());
+		self
+	}
+
+	// one preprocessor per subset of "features", along with the features enabled in it
+	// fails if the subsets can't be counted
+	pub fn permutations<'a>(&self, features : &[&'a str]) -> Result<Vec<(Vec<&'a str>, Preprocessor)>, GLError> {
+		let n_permutations = u32::try_from(features.len())
+			.ok()
+			.and_then(|n_features| 1usize.checked_shl(n_features))
+			.ok_or(GLError::TooManyFeatures(features.len()))?;
+		let mut to_return = Vec::with_capacity(n_permutations);
+		for mask in 0 .. n_permutations {
+			let enabled : Vec<&str> = features
+				.iter()
+				.enumerate()
+				.filter(|(i, _)| mask & (1 << i) != 0)
+				.map(|(_, feature)| *feature)
+				.collect();
+			let preprocessor = self.clone().features(&enabled);
+			to_return.push((enabled, preprocessor));
+		}
+		Ok(to_return)
+	}
+
+	pub fn process_file(&self, filepath : &str) -> Result<Preprocessed, GLError> {
+		let path = PathBuf::from(filepath);
+		let source = self.read(&path)?;
+		self.process(path, &source)
+	}
+
+	// "name" is used in diagnostics, and includes are resolved relative to it
+	pub fn process_str(&self, name : &str, source : &str) -> Result<Preprocessed, GLError> {
+		self.process(PathBuf::from(name), source)
+	}
+
+	fn process(&self, path : PathBuf, source : &str) -> Result<Preprocessed, GLError> {
+		let mut state = State {
+			version : None,
+			body    : Vec::new(),
+			files   : Vec::new(),
+			stack   : Vec::new(),
+			once    : Vec::new(),
+		};
+		state.include(self, path, source)?;
+
+		let version = self.version.as_ref().or(state.version.as_ref());
+		let line_offset = line_offset(version.map(String::as_str));
+
+		let mut output = Vec::with_capacity(state.body.len() + self.defines.len() + 1);
+		if let Some(version) = version {
+			output.push((format!("#version {}", version), None));
+		}
+		for (name, value) in &self.defines {
+			output.push((format!("#define {} {}", name, value), None));
+		}
+		output.extend(state.body.into_iter().map(|chunk| match chunk {
+			Chunk::Text(line, origin) => (line, origin),
+			Chunk::Line { line, file } => (format!("#line {} {}", line - line_offset, file), None),
+		}));
+
+		let mut source = String::new();
+		let mut lines  = Vec::with_capacity(output.len());
+		for (line, origin) in output {
+			source.push_str(&line);
+			source.push('\n');
+			lines.push(origin);
+		}
+		let files = state.files.iter().map(|file| file.to_string_lossy().into_owned()).collect();
+
+		Ok(Preprocessed { source, files, lines })
+	}
+
+	// include paths are tried relative to the including file, then as they are
+	fn resolve(&self, from : &Path, include : &str) -> Result<(PathBuf, String), GLError> {
+		let relative = normalize(&from.parent().unwrap_or(Path::new("")).join(include));
+		for candidate in [relative, normalize(Path::new(include))] {
+			if let Some(source) = self.virtual_files.get(&candidate) {
+				return Ok((candidate, source.clone()));
+			}
+			if candidate.is_file() {
+				let source = self.read(&candidate)?;
+				return Ok((candidate, source));
+			}
+		}
+		Err(GLError::IncludeNotFound(include.to_string()))
+	}
+
+	fn read(&self, path : &Path) -> Result<String, GLError> {
+		if let Some(source) = self.virtual_files.get(path) {
+			return Ok(source.clone());
+		}
+		Ok(std::fs::read_to_string(path)?)
+	}
+}
+
+
+// '#line' directives are only written once the version is known
+enum Chunk {
+	// with its origin, as in "Preprocessed::lines"
+	Text(String, Option<(usize, usize)>),
+	// the next line is line "line" of file number "file"
+	Line { line : usize, file : usize },
+}
+
+struct State {
+	version : Option<String>,
+	body    : Vec<Chunk>,
+	files   : Vec<PathBuf>,
+	// files being included, to detect cycles
+	stack   : Vec<PathBuf>,
+	// files with '#pragma once'
+	once    : Vec<PathBuf>,
+}
+
+impl State {
+	fn include(&mut self, preprocessor : &Preprocessor, path : PathBuf, source : &str) -> Result<(), GLError> {
+		if self.stack.contains(&path) {
+			return Err(GLError::IncludeCycle(path.to_string_lossy().into_owned()));
+		}
+		if self.once.contains(&path) {
+			return Ok(());
+		}
+
+		let file = self.files.len();
+		self.files.push(path.clone());
+		self.stack.push(path.clone());
+
+		self.body.push(Chunk::Line { line : 1, file });
+		for (i, line) in source.lines().enumerate() {
+			let line_no = i + 1;
+			let trimmed = line.trim_start();
+
+			if let Some(version) = directive(trimmed, "version") {
+				if self.stack.len() == 1 {
+					self.version = Some(version.to_string());
+				}
+				// keep numbering intact
+				self.body.push(Chunk::Text(String::new(), Some((file, line_no))));
+			}
+			else if directive(trimmed, "pragma").map(str::trim) == Some("once") {
+				self.once.push(path.clone());
+				self.body.push(Chunk::Text(String::new(), Some((file, line_no))));
+			}
+			else if let Some(include) = directive(trimmed, "include") {
+				let include = include.trim();
+				let include = include
+					.strip_prefix('"')
+					.and_then(|include| include.strip_suffix('"'))
+					.ok_or_else(|| GLError::IncludeNotFound(include.to_string()))?;
+				let (include_path, include_source) = preprocessor.resolve(&path, include)?;
+				self.include(preprocessor, include_path, &include_source)?;
+				self.body.push(Chunk::Line { line : line_no + 1, file });
+			}
+			else {
+				self.body.push(Chunk::Text(line.to_string(), Some((file, line_no))));
+			}
+		}
+
+		self.stack.pop();
+		Ok(())
+	}
+}
+
+// what to subtract from the number of the next line in '#line' directives
+// sources without '#version' are GLSL 1.10
+fn line_offset(version : Option<&str>) -> usize {
+	let number = version
+		.and_then(|version| version.split_whitespace().next())
+		.and_then(|number| number.parse::<u32>().ok())
+		.unwrap_or(110);
+	if number < 420 { 1 } else { 0 }
+}
+
+// lexically removes "." and ".." components, so that virtual files can be found
+fn normalize(path : &Path) -> PathBuf {
+	use std::path::Component;
+	let mut to_return = PathBuf::new();
+	for component in path.components() {
+		match component {
+			Component::CurDir    => {},
+			Component::ParentDir => {
+				let can_pop = matches!(to_return.components().next_back(), Some(Component::Normal(_)));
+				if can_pop { to_return.pop(); }
+				else       { to_return.push(".."); }
+			},
+			component => to_return.push(component),
+		}
+	}
+	to_return
+}
+
+// "#  name rest" -> Some("rest")
+fn directive<'a>(line : &'a str, name : &str) -> Option<&'a str> {
+	let rest = line.strip_prefix('#')?.trim_start().strip_prefix(name)?;
+	if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+		Some(rest.trim_start())
+	}
+	else {
+		None
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn version_and_defines_come_first() {
+		let result = Preprocessor::new()
+			.define("N_LIGHTS", "4")
+			.process_str("main.glsl", "#version 330 core\nvoid main() {}\n")
+			.unwrap();
+
+		let lines : Vec<&str> = result.source.lines().collect();
+		assert_eq!(lines[0], "#version 330 core");
+		assert_eq!(lines[1], "#define N_LIGHTS 4");
+		// numbers the next line 1 in GLSL 3.30
+		assert_eq!(lines[2], "#line 0 0");
+		assert_eq!(result.original(5), Some(("main.glsl", 2)));
+		assert_eq!(result.original(1), None);
+	}
+
+	#[test]
+	fn version_override() {
+		let result = Preprocessor::new()
+			.version("450")
+			.process_str("main.glsl", "#version 330 core\n")
+			.unwrap();
+		assert!(result.source.starts_with("#version 450\n"));
+		assert_eq!(result.source.matches("#version").count(), 1);
+	}
+
+	#[test]
+	fn include_is_resolved_relative_to_file() {
+		let result = Preprocessor::new()
+			.virtual_file("shaders/common/light.glsl", "#pragma once\nfloat light() { return 1.0; }")
+			.virtual_file("shaders/jug/frag.glsl", "#version 330 core\n#include \"../common/light.glsl\"\n#include \"../common/light.glsl\"\nvoid main() {}")
+			.process_file("shaders/jug/frag.glsl")
+			.unwrap();
+
+		assert_eq!(result.source.matches("float light()").count(), 1);
+		assert_eq!(result.files.len(), 2);
+
+		let light_line = result.source.lines().position(|line| line.starts_with("float light()")).unwrap() + 1;
+		assert_eq!(result.original(light_line), Some(("shaders/common/light.glsl", 2)));
+
+		let main_line = result.source.lines().position(|line| line.starts_with("void main()")).unwrap() + 1;
+		assert_eq!(result.original(main_line), Some(("shaders/jug/frag.glsl", 4)));
+		assert!(result.source.contains("#line 3 0\n"));
+	}
+
+	#[test]
+	fn line_directives_follow_the_version() {
+		let source = "#version 330 core\n#include \"common.glsl\"\nvoid main() {}";
+		let preprocessor = Preprocessor::new().virtual_file("common.glsl", "float one() { return 1.0; }");
+
+		let result = preprocessor.process_str("main.glsl", source).unwrap();
+		assert!(result.source.contains("#line 0 1\n"));
+		assert!(result.source.contains("#line 2 0\n"));
+
+		// '#line N' numbers the next line N from GLSL 4.20 on
+		let result = preprocessor.clone().version("450 core").process_str("main.glsl", source).unwrap();
+		assert!(result.source.contains("#line 1 1\n"));
+		assert!(result.source.contains("#line 3 0\n"));
+
+		let result = preprocessor.process_str("main.glsl", "void main() {}").unwrap();
+		assert!(result.source.starts_with("#line 0 0\n"));
+	}
+
+	#[test]
+	fn include_cycle_is_an_error() {
+		let result = Preprocessor::new()
+			.virtual_file("a.glsl", "#include \"b.glsl\"")
+			.virtual_file("b.glsl", "#include \"a.glsl\"")
+			.process_file("a.glsl");
+		assert!(matches!(result, Err(GLError::IncludeCycle(_))));
+	}
+
+	#[test]
+	fn missing_include_is_an_error() {
+		let result = Preprocessor::new()
+			.process_str("main.glsl", "#include \"nowhere.glsl\"");
+		assert!(matches!(result, Err(GLError::IncludeNotFound(_))));
+	}
+
+	#[test]
+	fn lit_shaders_share_lighting() {
+		for path in ["resources/shaders/diffuse/frag.glsl", "resources/shaders/jug/frag.glsl"] {
+			let result = Preprocessor::new().process_file(path).unwrap();
+			assert!(result.source.starts_with("#version 330 core\n"));
+			assert!(result.source.contains("float light_intensity("));
+			assert_eq!(result.files[1], "resources/shaders/common/lighting.glsl");
+		}
+	}
+
+	#[test]
+	fn permutations_cover_every_subset() {
+		let permutations = Preprocessor::new().permutations(&["SHADOWS", "TEXTURED"]).unwrap();
+		assert_eq!(permutations.len(), 4);
+
+		let (enabled, preprocessor) = &permutations[3];
+		assert_eq!(enabled, &vec!["SHADOWS", "TEXTURED"]);
+		let result = preprocessor.process_str("main.glsl", "").unwrap();
+		assert!(result.source.contains("#define SHADOWS 1\n"));
+		assert!(result.source.contains("#define TEXTURED 1\n"));
+	}
+
+	#[test]
+	fn too_many_permutations_is_an_error() {
+		let features = ["FEATURE"; usize::BITS as usize];
+		let permutations = Preprocessor::new().permutations(&features);
+		assert!(matches!(permutations, Err(GLError::TooManyFeatures(n)) if n == features.len()));
+	}
+}
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::error::*;
+use crate::query::*;
+
+
+
+// -- GPU PROFILER
+// times labelled sections of a frame with TIME_ELAPSED queries
+// results are read a few frames later, so that profiling never stalls the pipeline
+
+
+#[derive(Debug)]
+pub struct GPUProfiler {
+	current     : Vec<(String, Query)>,
+	in_flight   : VecDeque<Vec<(String, Query)>>,
+	free        : Vec<Query>,
+	last_report : Option<Vec<(String, Duration)>>,
+}
+
+impl GPUProfiler {
+	// frames whose results are still unavailable after that many frames are dropped
+	pub const MAX_FRAMES_IN_FLIGHT : usize = 4;
+
+	pub fn new() -> Self {
+		Self {
+			current     : Vec::new(),
+			in_flight   : VecDeque::with_capacity(Self::MAX_FRAMES_IN_FLIGHT),
+			free        : Vec::new(),
+			last_report : None,
+		}
+	}
+
+	// times the GPU work issued by "f", e.g. a "Program::draw_buffer" call
+	// sections cannot be nested
+	pub fn section<F, R>(&mut self, label : &str, f : F) -> Result<R, GLError>
+	where F : FnOnce() -> R
+	{
+		let query = match self.free.pop() {
+			Some(query) => query,
+			None        => Query::new(QueryKind::TimeElapsed)?,
+		};
+		let result = query.measure(f)?;
+		self.current.push((label.to_string(), query));
+		Ok(result)
+	}
+
+	pub fn end_frame(&mut self) {
+		let frame = std::mem::take(&mut self.current);
+		self.in_flight.push_back(frame);
+
+		while let Some(frame) = self.in_flight.front() {
+			if !frame.iter().all(|(_, query)| query.is_available()) {
+				break;
+			}
+			let frame = self.in_flight.pop_front().unwrap();
+			let mut report = Vec::with_capacity(frame.len());
+			for (label, query) in frame {
+				report.push((label, Duration::from_nanos(query.result())));
+				self.free.push(query);
+			}
+			self.last_report = Some(report);
+		}
+
+		while self.in_flight.len() > Self::MAX_FRAMES_IN_FLIGHT {
+			let frame = self.in_flight.pop_front().unwrap();
+			self.free.extend(frame.into_iter().map(|(_, query)| query));
+		}
+	}
+
+	// timings of the latest frame whose results came back, in the order the sections were issued
+	pub fn last_report(&self) -> Option<&[(String, Duration)]> {
+		self.last_report.as_deref()
+	}
+}
+
+impl Default for GPUProfiler {
+	fn default() -> Self { Self::new() }
+}
@@ -9,12 +9,21 @@ use crate::texture::*;
 use crate::attributes::*;
 use crate::error::*;
 use crate::log::*;
-
-use std::cell::Cell;
+use crate::indirect::*;
+use crate::query::*;
+use crate::diagnostics::*;
+use crate::interface::*;
+use crate::pipeline::*;
+use crate::debug;
+use crate::dsa;
+use crate::capabilities::{self, Feature};
+
+use std::cell::{Cell, RefCell};
 use std::ffi::CString;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::rc::Rc;
+use std::ops::Range;
 
 
 // -- Program
@@ -25,10 +34,14 @@ pub struct ProgramId(pub GLuint);
 
 
 pub struct ProgramBuilder {
-	vert_shader       : Shader<Vertex>,
-	frag_shader       : Shader<Fragment>,
+	vert_shader       : Option<Shader<Vertex>>,
+	frag_shader       : Option<Shader<Fragment>>,
 	maybe_geom_shader : Option<Shader<Geometry>>,
 	attributes        : Option<Vec<String>>,
+	feedback_varyings : Option<(Vec<String>, FeedbackMode)>,
+	retrievable_binary : bool,
+	separable         : bool,
+	label             : Option<String>,
 }
 
 impl ProgramBuilder {
@@ -37,9 +50,42 @@ impl ProgramBuilder {
 		frag_shader       : Shader<Fragment>,
 		) -> Self {
 		Self {
+			vert_shader       : Some(vert_shader),
+			frag_shader       : Some(frag_shader),
+			maybe_geom_shader : None,
+			attributes        : None,
+			feedback_varyings : None,
+			retrievable_binary : false,
+			separable         : false,
+			label             : None,
+		}
+	}
+
+
+	// programs with a single stage, to be combined in a "ProgramPipeline"
+	pub fn vertex_stage(vert_shader : Shader<Vertex>) -> Self {
+		Self {
+			vert_shader       : Some(vert_shader),
+			frag_shader       : None,
+			maybe_geom_shader : None,
+			attributes        : None,
+			feedback_varyings : None,
+			retrievable_binary : false,
+			separable         : true,
+			label             : None,
+		}
+	}
+
+	pub fn fragment_stage(frag_shader : Shader<Fragment>) -> Self {
+		Self {
+			vert_shader       : None,
+			frag_shader       : Some(frag_shader),
 			maybe_geom_shader : None,
 			attributes        : None,
-			vert_shader, frag_shader,
+			feedback_varyings : None,
+			retrievable_binary : false,
+			separable         : true,
+			label             : None,
 		}
 	}
 
@@ -62,6 +108,36 @@ impl ProgramBuilder {
 	}
 
 
+	// outputs of the last vertex processing stage to capture with "Program::capture_into"
+	pub fn feedback_varyings(mut self, varyings : &[&str], mode : FeedbackMode) -> Self {
+		let varyings = varyings.iter().map(|varying| varying.to_string()).collect();
+		self.feedback_varyings = Some((varyings, mode));
+		self
+	}
+
+
+	// the stages of the program can be used in a "ProgramPipeline"
+	pub fn separable(mut self) -> Self {
+		self.separable = true;
+		self
+	}
+
+
+	// hints the driver that "Program::binary" will be called
+	pub fn retrievable_binary(mut self) -> Self {
+		self.retrievable_binary = true;
+		self
+	}
+
+
+	// shown by debugging tools, see "debug"
+	pub fn label(mut self, label : &str) -> Self {
+		self.label = Some(label.to_string());
+		self
+	}
+
+
+	#[track_caller]
 	pub fn build(self) -> Result<Program, GLError> {
 		Program::new(self)
 	}
@@ -74,17 +150,25 @@ pub struct AttributePos(pub gl::types::GLuint);
 #[derive(Debug)]
 pub struct Program {
 	pub id: ProgramId,
-	has_geometry: bool,
+	stages: PipelineStages,
+	separable: bool,
 	textures: HashMap<String, (usize, Texture)>,
 	indices : Option<AnyBuffer>,
+	primitive_restart : Option<GLuint>,
 	vao: VAOId,
+	// views bound to each attribute, to replay them on another program
+	views: RefCell<HashMap<String, BufferView>>,
 	n_elems: Cell<Option<usize>>,
 	attributes_loc : Rc<HashMap<String, AttributePos>>,
 	max_n_tex_units : usize,
+	warnings : Vec<Diagnostic>,
+	// number of buffers transform feedback writes to, see "capture_into"
+	feedback_buffers : usize,
 }
 
 impl Program {
 
+	#[track_caller]
 	pub fn new(
 		builder : ProgramBuilder
 		) -> Result<Self, GLError> 
@@ -94,49 +178,215 @@ impl Program {
 			frag_shader,
 			maybe_geom_shader,
 			attributes,
+			feedback_varyings,
+			retrievable_binary,
+			separable,
+			label,
 		} = builder;
 
 		// -- Check that number of texture provided is below max (prior to everything)
-		let mut max_n_tex_units : gl::types::GLint = -1;
-		unsafe {gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max_n_tex_units)};
-		if max_n_tex_units == -1 {
-			return Err(GLError::CannotGetMaxTexUnits)
-		}
+		let max_n_tex_units = Self::max_n_tex_units()?;
+
+		// -- Check feedback varyings can be passed to GL, before creating anything
+		let feedback_varyings = match feedback_varyings {
+			Some((varyings, mode)) => {
+				let varyings_c = varyings
+					.into_iter()
+					.map(|varying| CString::new(varying).map_err(|err| {
+						GLError::NameContainsNul(String::from_utf8_lossy(&err.into_vec()).into_owned())
+					}))
+					.collect::<Result<Vec<CString>, GLError>>()?;
+				Some((varyings_c, mode))
+			},
+			None => None,
+		};
+		let feedback_buffers = match &feedback_varyings {
+			Some((varyings, FeedbackMode::Separate)) => varyings.len(),
+			_                                        => 1,
+		};
 
 
 		let program_id = unsafe {gl::CreateProgram()};
 
 		// -- Attach shaders
-		unsafe {
-			gl::AttachShader(program_id, vert_shader.id);
-			gl::AttachShader(program_id, frag_shader.id);
-			maybe_geom_shader.as_ref().map_or((), |geom_shader|
-				gl::AttachShader(program_id, geom_shader.id)
+		let shader_ids : Vec<GLuint> = [
+			vert_shader.as_ref().map(|shader| shader.id),
+			frag_shader.as_ref().map(|shader| shader.id),
+			maybe_geom_shader.as_ref().map(|shader| shader.id),
+		].into_iter().flatten().collect();
+		for shader_id in &shader_ids {
+			unsafe {gl::AttachShader(program_id, *shader_id)};
+		}
+
+		let mut stages = PipelineStages::NONE;
+		if vert_shader.is_some()       { stages = stages | PipelineStages::VERTEX;   }
+		if frag_shader.is_some()       { stages = stages | PipelineStages::FRAGMENT; }
+		if maybe_geom_shader.is_some() { stages = stages | PipelineStages::GEOMETRY; }
+
+		// -- Declare transform feedback varyings (must happen before linking)
+		if let Some((varyings_c, mode)) = &feedback_varyings {
+			let varyings_ptr : Vec<*const GLchar> = varyings_c
+				.iter()
+				.map(|varying| varying.as_ptr())
+				.collect();
+			unsafe {
+				gl::TransformFeedbackVaryings(
+					program_id,
+					varyings_ptr.len() as GLsizei,
+					varyings_ptr.as_ptr(),
+					mode.to_gl(),
 				);
-			gl::LinkProgram(program_id);
-		}		
+			}
+		}
+
+		if retrievable_binary {
+			unsafe {gl::ProgramParameteri(program_id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint)};
+		}
+		if separable {
+			unsafe {gl::ProgramParameteri(program_id, gl::PROGRAM_SEPARABLE, gl::TRUE as GLint)};
+		}
 
 		// -- Link and check link
 		unsafe {gl::LinkProgram(program_id)};
-		let log_result = get_log(program_id, LogKind::LinkLog);
+		let (success, log) = get_status_and_log(program_id, LogKind::LinkLog);
 
-		if let Some(error_msg) = log_result {
-			return Err(GLError::LinkProgram(error_msg));
+		if !success {
+			unsafe {gl::DeleteProgram(program_id)};
+			return Err(GLError::LinkProgram(parse_log(&log, Stage::Link, Severity::Error, None)));
 		}
+		let warnings = parse_log(&log, Stage::Link, Severity::Warning, None);
 
 
 		// -- Detach shaders
+		for shader_id in &shader_ids {
+			unsafe {gl::DetachShader(program_id, *shader_id)};
+		}
+
+		if let Some(label) = label {
+			debug::set_label(gl::PROGRAM, program_id, &label);
+		}
+		check("Program::new").inspect_err(|_| unsafe {gl::DeleteProgram(program_id)})?;
+
+		let mut program = Self::from_linked(ProgramId(program_id), stages, separable, attributes, max_n_tex_units, warnings)
+			.inspect_err(|_| unsafe {gl::DeleteProgram(program_id)})?;
+		program.feedback_buffers = feedback_buffers;
+		Ok(program)
+	}
+
+
+	// loads the output of "Program::binary", fails with "GLError::LinkProgram" if the driver rejects it
+	// (e.g. the binary comes from another driver version)
+	#[track_caller]
+	pub fn from_binary(
+		format     : GLenum,
+		binary     : &[u8],
+		stages     : PipelineStages,
+		separable  : bool,
+		attributes : Option<Vec<String>>,
+		) -> Result<Self, GLError>
+	{
+		capabilities::require(Feature::ProgramBinary)?;
+		let max_n_tex_units = Self::max_n_tex_units()?;
+
+		let program_id = unsafe {gl::CreateProgram()};
 		unsafe {
-			gl::DetachShader(program_id, vert_shader.id);
-			gl::DetachShader(program_id, frag_shader.id);
-			maybe_geom_shader.as_ref().map_or((), |geom_shader|
-				gl::DetachShader(program_id, geom_shader.id)
-				);
+			if separable {
+				gl::ProgramParameteri(program_id, gl::PROGRAM_SEPARABLE, gl::TRUE as GLint);
+			}
+			gl::ProgramBinary(program_id, format, binary.as_ptr().cast(), binary.len() as GLsizei);
+		}
+		let (success, log) = get_status_and_log(program_id, LogKind::LinkLog);
+
+		if !success {
+			unsafe {gl::DeleteProgram(program_id)};
+			return Err(GLError::LinkProgram(parse_log(&log, Stage::Link, Severity::Error, None)));
+		}
+		let warnings = parse_log(&log, Stage::Link, Severity::Warning, None);
+		// the feedback varyings are restored with the binary
+		let feedback_buffers = Self::feedback_buffers_of(program_id);
+		check("Program::from_binary").inspect_err(|_| unsafe {gl::DeleteProgram(program_id)})?;
+
+		let mut program = Self::from_linked(ProgramId(program_id), stages, separable, attributes, max_n_tex_units, warnings)
+			.inspect_err(|_| unsafe {gl::DeleteProgram(program_id)})?;
+		program.feedback_buffers = feedback_buffers;
+		Ok(program)
+	}
+
+
+	// driver-specific binary of the program, and its format
+	// None if the driver can't provide one, best built with "ProgramBuilder::retrievable_binary"
+	pub fn binary(&self) -> Option<(GLenum, Vec<u8>)> {
+		capabilities::require(Feature::ProgramBinary).ok()?;
+		let mut length = 0;
+		unsafe {gl::GetProgramiv(self.id.0, gl::PROGRAM_BINARY_LENGTH, &mut length)};
+		if length <= 0 {
+			return None;
+		}
+
+		let mut binary  = vec![0u8; length as usize];
+		let mut written = 0;
+		let mut format  = 0;
+		unsafe {
+			gl::GetProgramBinary(self.id.0, length, &mut written, &mut format, binary.as_mut_ptr().cast());
+		}
+		if written <= 0 {
+			return None;
 		}
+		binary.truncate(written as usize);
+		Some((format, binary))
+	}
+
+
+	pub fn has_geometry(&self) -> bool { self.stages.contains(PipelineStages::GEOMETRY) }
+
+
+	pub fn stages(&self) -> PipelineStages { self.stages }
+
+
+	pub fn is_separable(&self) -> bool { self.separable }
+
+
+	pub fn attribute_names(&self) -> Vec<String> {
+		self.attributes_loc.keys().cloned().collect()
+	}
+
+
+	fn max_n_tex_units() -> Result<usize, GLError> {
+		if let Some(capabilities) = capabilities::current() {
+			return Ok(capabilities.max_combined_texture_units);
+		}
+		let mut max_n_tex_units : gl::types::GLint = -1;
+		unsafe {gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max_n_tex_units)};
+		if max_n_tex_units == -1 {
+			return Err(GLError::CannotGetMaxTexUnits)
+		}
+		Ok(max_n_tex_units as usize)
+	}
+
 
+	// as computed by "Program::new" from the feedback varyings
+	fn feedback_buffers_of(program_id : GLuint) -> usize {
+		let (mut mode, mut n_varyings) = (0, 0);
+		unsafe {
+			gl::GetProgramiv(program_id, gl::TRANSFORM_FEEDBACK_BUFFER_MODE, &mut mode);
+			gl::GetProgramiv(program_id, gl::TRANSFORM_FEEDBACK_VARYINGS, &mut n_varyings);
+		}
+		if mode as GLenum == gl::SEPARATE_ATTRIBS { n_varyings.max(1) as usize } else { 1 }
+	}
+
+
+	// everything that follows a successful link
+	fn from_linked(
+		id              : ProgramId,
+		stages          : PipelineStages,
+		separable       : bool,
+		attributes      : Option<Vec<String>>,
+		max_n_tex_units : usize,
+		warnings        : Vec<Diagnostic>,
+		) -> Result<Self, GLError>
+	{
 		// -- Find attribute location
 
-		let id = ProgramId(program_id);
 		let attributes_loc = 
 			if let Some(attributes) = attributes {
 				Self::from_attribute_names(id, attributes)?
@@ -147,23 +397,27 @@ impl Program {
 
 
 		// -- Generate default vao
-		let mut vao_id = 0;
-		unsafe {gl::GenVertexArrays(1, &mut vao_id);}
+		let vao_id = dsa::create_vertex_array();
 		if vao_id == 0 {
 			return Err(GLError::CouldNotCreateVAO);
 		}
 		let vao_id = VAOId(vao_id);
 
 
-		let mut to_return = Self {
+		let to_return = Self {
 			id,
-			has_geometry : maybe_geom_shader.is_some(),
+			stages,
+			separable,
 			vao :  vao_id,
 			attributes_loc : Rc::new(attributes_loc),
 			textures : HashMap::new(),
+			views: RefCell::new(HashMap::new()),
 			n_elems: Cell::new(None),
 			indices: None,
-			max_n_tex_units : max_n_tex_units.try_into().unwrap(),
+			primitive_restart: None,
+			max_n_tex_units,
+			warnings,
+			feedback_buffers : 1,
 		};
 
 
@@ -217,6 +471,7 @@ impl Program {
 		Ok(to_return)
 	}
 
+	#[track_caller]
 	pub fn texture(&mut self, name : &str, texture : Texture) -> Result<(), GLError> {
 		let i = match self.textures.get_mut(name) {
 			Some((i, old_texture)) => {
@@ -229,8 +484,7 @@ impl Program {
 				n
 			},
 		};
-		self.uniform(name)?.pass(&(i as gl::types::GLint));
-		Ok(())
+		self.uniform(name)?.pass(&Sampler(i as GLint))
 	}
 
 	fn from_attribute_names(id : ProgramId, attribute_names : Vec<String>) -> Result<HashMap<String, AttributePos>, GLError>
@@ -255,44 +509,57 @@ impl Program {
 	}
 
 
+	#[track_caller]
 	pub fn duplicate(&self) -> Result<Self, GLError> {
-		let mut vao_id = 0;
-		unsafe {gl::GenVertexArrays(1, &mut vao_id);}
+		let vao_id = dsa::create_vertex_array();
 		if vao_id == 0 {
 			return Err(GLError::CouldNotCreateVAO);
 		}
+		check("Program::duplicate").inspect_err(|_| unsafe {gl::DeleteVertexArrays(1, &vao_id)})?;
 		let vao_id = VAOId(vao_id);
 
 
 
 		return Ok(Self {
 			id: self.id,
-			has_geometry: self.has_geometry,
+			stages: self.stages,
+			separable: self.separable,
 			textures: self.textures.clone(),
 			vao: vao_id,
 			attributes_loc: self.attributes_loc.clone(),
+			views: RefCell::new(HashMap::new()),
 			n_elems: Cell::new(None),
     		indices: None,
+    		primitive_restart: self.primitive_restart,
     		max_n_tex_units: self.max_n_tex_units,
+    		warnings: self.warnings.clone(),
+    		feedback_buffers: self.feedback_buffers,
 		});
 	}
 
 
+	// fails without touching GL state if the view reads past the end of its buffer
+	#[track_caller]
 	pub fn bind(&self, attribute : &str, buffer_view : BufferView) -> Result<(), GLError> {
 		let attribute = attribute.to_string();
 		if let Some(pos) = self.attributes_loc.get(&attribute) {
-			unsafe {gl::BindVertexArray(self.vao.0);}
-
-			let new_val = Some(buffer_view.n_elems);
-			buffer_view.bind_to(*pos);
-
-			unsafe {gl::EnableVertexAttribArray(pos.0); }
-			unsafe {gl::BindVertexArray(0);}
+			buffer_view.validate()?;
+
+			let new_val = Some(buffer_view.n_elems());
+			self.views.borrow_mut().insert(attribute.clone(), buffer_view.clone());
+			dsa::vertex_attribute(
+				self.vao.0,
+				pos.0,
+				buffer_view.buffer_id().0,
+				buffer_view.stride(),
+				buffer_view.offset(),
+				buffer_view.data_info(),
+			);
 
 			if self.n_elems.get() < new_val {
 				self.n_elems.set(new_val)
 			}
-			Ok(())
+			check("Program::bind")
 		}
 		else {
 			Err(GLError::InexistentOrUndeclaredAttribute(attribute.to_string()))
@@ -310,6 +577,13 @@ impl Program {
 	}
 
 
+	// when set, any index equal to "restart_index" ends the current primitive in indexed draws
+	pub fn primitive_restart(&mut self, restart_index : Option<GLuint>) {
+		self.primitive_restart = restart_index;
+	}
+
+
+	#[track_caller]
 	pub fn uniform<'a, T : UniformData + ?Sized>(&'a self, uniform_name : &str) -> Result<Uniform<'a, T>, GLError> {
 
 		// shouldn't panic if uniform name contains no null bytes
@@ -323,19 +597,64 @@ impl Program {
 			Err(GLError::InexistentUniform(uniform_name.to_string()))
 		}
 		else {
+			// only read by the type check of "Uniform::pass", in debug builds
+			let gl_type = if cfg!(debug_assertions) { self.uniform_type(&uniform_name_c) } else { 0 };
+			check("Program::uniform")?;
 			Ok(Uniform::<'a, T> {
 				program_id : &self.id,
 				location   : LayoutLocation(location),
+				gl_type,
 				phantom    : std::marker::PhantomData,
 			})
 		}
 	}
 
 
+	// GL type of an active uniform, 0 if not found
+	fn uniform_type(&self, uniform_name_c : &CString) -> GLenum {
+		let mut index = gl::INVALID_INDEX;
+		unsafe {gl::GetUniformIndices(self.id.0, 1, &uniform_name_c.as_ptr(), &mut index)};
+		if index == gl::INVALID_INDEX {
+			return 0;
+		}
+		let mut gl_type = 0;
+		unsafe {gl::GetActiveUniformsiv(self.id.0, 1, &index, gl::UNIFORM_TYPE, &mut gl_type)};
+		gl_type as GLenum
+	}
+
+
+	// gives this program the attribute bindings, textures, indices and uniform values of "old"
+	// meant for a new version of the same program ; what doesn't exist anymore is skipped
+	pub(crate) fn restore_state_from(&mut self, old : &Program) {
+		for (attribute, view) in old.views.borrow().iter() {
+			let _ = self.bind(attribute, view.clone());
+		}
+		self.textures = old.textures.clone();
+		self.indices  = old.indices.clone();
+		self.primitive_restart = old.primitive_restart;
+		copy_uniforms(old.id, self.id);
+		copy_uniform_block_bindings(old.id, self.id);
+	}
+
+
+	// frees the program & its VAO ; duplicates of this program become invalid
+	pub(crate) fn delete(self) {
+		unsafe {
+			gl::DeleteVertexArrays(1, &self.vao.0);
+			gl::DeleteProgram(self.id.0);
+		}
+	}
+
+
+	// diagnostics of a successful link
+	pub fn warnings(&self) -> &[Diagnostic] { &self.warnings }
+
+
 	pub fn set_current(&self) {
 		unsafe {gl::UseProgram(self.id.0)}
 	}
 
+	#[track_caller]
 	pub fn bind_uniform<D>(&self, name : &str, uniform_buffer : &UniformBuffer<D>) -> Result<(), GLError>
 	{
 		let uniform_name_c : CString = CString::new(name.to_string()).unwrap();
@@ -352,11 +671,42 @@ impl Program {
 			gl::UniformBlockBinding(self.id.0, uniform_index, uniform_buffer.binding_point.0);
 		}
 
-		Ok(())
+		check("Program::bind_uniform")
+	}
+
+	// -- typed counterparts of "bind", "uniform", "texture" & "bind_uniform", see "interface::generate"
+
+	// fails if the view doesn't hold the attribute's type
+	#[track_caller]
+	pub fn bind_typed<T : GPUData>(&self, attribute : AttributeName<T>, buffer_view : BufferView) -> Result<(), GLError> {
+		if *buffer_view.data_info() != T::INFO {
+			return Err(GLError::MismatchedAttributeType(attribute.name.to_string()));
+		}
+		self.bind(attribute.name, buffer_view)
+	}
+
+	#[track_caller]
+	pub fn uniform_typed<T : UniformData + ?Sized>(&self, uniform : UniformName<T>) -> Result<Uniform<'_, T>, GLError> {
+		self.uniform(uniform.name)
+	}
+
+	#[track_caller]
+	pub fn texture_typed(&mut self, texture_name : TextureName, texture : Texture) -> Result<(), GLError> {
+		self.texture(texture_name.name, texture)
+	}
+
+	#[track_caller]
+	pub fn bind_uniform_typed<D>(&self, block : BlockName, uniform_buffer : &UniformBuffer<D>) -> Result<(), GLError> {
+		self.bind_uniform(block.name, uniform_buffer)
+	}
+
+	// unit & texture name of each texture set with "texture"
+	pub(crate) fn texture_units(&self) -> impl Iterator<Item = (usize, GLuint)> + '_ {
+		self.textures.values().map(|(unit, texture)| (*unit, texture.id.0))
 	}
 
 	#[inline]
-	fn bind_texture(&self) {
+	pub(crate) fn bind_texture(&self) {
 		for (i, texture) in self.textures.values() {
 			let try_into : gl::types::GLenum = (*i).try_into().unwrap();
 			unsafe {gl::ActiveTexture(gl::TEXTURE0 + try_into);}
@@ -369,58 +719,392 @@ impl Program {
 		unsafe {gl::BindTexture(gl::TEXTURE_2D, 0);}
 	}
 
+	#[track_caller]
 	pub fn draw_buffer(&self, mode : DrawMode) -> Result<(), GLError> {
 		if let Some(indices) = &self.indices {
-			self.draw_indexed_buffer(indices, mode);
+			self.draw_indexed_buffer(indices, 0, indices.n_elems, None, &mode);
 		}
 		else {
 			let n_elems = self.n_elems.get().ok_or(GLError::NoBufferAttached)?;
-			self.draw_buffer_partial(0, n_elems, mode);
+			self.draw_array_buffer(0, n_elems, &mode);
+		}
+		check("Program::draw_buffer")
+	}
+
+	// draws "how_many" elements starting from "from"
+	// if indices are set, the range is taken in the index buffer; otherwise, in the attribute buffers
+	#[track_caller]
+	pub fn draw_buffer_partial(&self, from : usize, how_many : usize, mode : DrawMode) -> Result<(), GLError> {
+		if let Some(indices) = &self.indices {
+			Self::check_range(from, how_many, indices.n_elems)?;
+			self.draw_indexed_buffer(indices, from, how_many, None, &mode);
+		}
+		else {
+			let n_elems = self.n_elems.get().ok_or(GLError::NoBufferAttached)?;
+			Self::check_range(from, how_many, n_elems)?;
+			self.draw_array_buffer(from, how_many, &mode);
+		}
+		check("Program::draw_buffer_partial")
+	}
+
+	// draws "how_many" indices starting from "from", adding "base_vertex" to every index fetched
+	// useful when several meshes are packed into one vertex buffer but indexed from 0
+	#[track_caller]
+	pub fn draw_buffer_base_vertex(&self, from : usize, how_many : usize, base_vertex : GLint, mode : DrawMode) -> Result<(), GLError> {
+		let indices = self.indices.as_ref().ok_or(GLError::NoIndexBufferAttached)?;
+		Self::check_range(from, how_many, indices.n_elems)?;
+		self.draw_indexed_buffer(indices, from, how_many, Some(base_vertex), &mode);
+		check("Program::draw_buffer_base_vertex")
+	}
+
+	// "ranges" are pairs of (from, how_many), in the index buffer if indices are set
+	#[track_caller]
+	pub fn draw_buffer_partial_multi(&self, ranges : &[(usize, usize)], mode : DrawMode) -> Result<(), GLError> {
+		let bound = match &self.indices {
+			Some(indices) => indices.n_elems,
+			None          => self.n_elems.get().ok_or(GLError::NoBufferAttached)?,
+		};
+		for (from, how_many) in ranges {
+			Self::check_range(*from, *how_many, bound)?;
+		}
+
+		self.bind_texture();
+		unsafe {gl::BindVertexArray(self.vao.0);}
+		let counts = ranges.iter().map(|(_, y)| *y as gl::types::GLsizei).collect::<Vec<_>>();
+		if let Some(indices) = &self.indices {
+			let index_size = indices.gpu_info.gl_type.size();
+			let offsets = ranges.iter().map(|(x, _)| (*x * index_size) as *const std::os::raw::c_void).collect::<Vec<_>>();
+			unsafe {gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, indices.id().0);}
+			self.enable_primitive_restart();
+			unsafe {
+				gl::MultiDrawElements(
+					mode.to_gl(), 
+					counts.as_ptr(), 
+					indices.gpu_info.gl_type.to_opengl_sym(), 
+					offsets.as_ptr(), 
+					ranges.len() as gl::types::GLsizei
+					);
+			}
+			self.disable_primitive_restart();
+		}
+		else {
+			let starts = ranges.iter().map(|(x, _)| *x as gl::types::GLint).collect::<Vec<_>>();
+			unsafe {
+				gl::MultiDrawArrays(
+					mode.to_gl(), 
+					starts.as_ptr(), 
+					counts.as_ptr(), 
+					ranges.len() as gl::types::GLsizei
+					);
+			}
+		}
+		self.unbind_texture();
+		check("Program::draw_buffer_partial_multi")
+	}
+
+	// draws with the parameters stored at position "index" of "commands"
+	// the parameters may have been written by the GPU itself (e.g. by a culling pass)
+	#[track_caller]
+	pub fn draw_indirect<C : IndirectCommand>(&self, commands : &Buffer<C>, index : usize, mode : DrawMode) -> Result<(), GLError> {
+		capabilities::require(Feature::DrawIndirect)?;
+		let end = index.checked_add(1).ok_or(GLError::DrawRangeOutOfBounds)?;
+		self.issue_indirect(commands, index .. end, mode, false)?;
+		check("Program::draw_indirect")
+	}
+
+	#[track_caller]
+	pub fn multi_draw_indirect<C : IndirectCommand>(&self, commands : &Buffer<C>, range : Range<usize>, mode : DrawMode) -> Result<(), GLError> {
+		capabilities::require(Feature::MultiDrawIndirect)?;
+		self.issue_indirect(commands, range, mode, true)?;
+		check("Program::multi_draw_indirect")
+	}
+
+	// without "multi", "range" holds a single command
+	fn issue_indirect<C : IndirectCommand>(&self, commands : &Buffer<C>, range : Range<usize>, mode : DrawMode, multi : bool) -> Result<(), GLError> {
+		if commands.kind() != BufferKind::DrawIndirect {
+			return Err(GLError::IsntDrawIndirectBuffer);
+		}
+		Self::check_range(range.start, range.len(), commands.len())?;
+		let offset = (range.start * std::mem::size_of::<C>()) as *const std::os::raw::c_void;
+		if C::INDEXED && self.indices.is_none() {
+			return Err(GLError::NoIndexBufferAttached);
+		}
+
+		self.bind_texture();
+		unsafe {gl::BindVertexArray(self.vao.0);}
+		unsafe {gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, commands.id().0);}
+		if let (true, Some(indices)) = (C::INDEXED, &self.indices) {
+			unsafe {gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, indices.id().0);}
+			self.enable_primitive_restart();
+			let index_type = indices.gpu_info.gl_type.to_opengl_sym();
+			unsafe {
+				if multi {
+					gl::MultiDrawElementsIndirect(mode.to_gl(), index_type, offset, range.len() as gl::types::GLsizei, 0);
+				}
+				else {
+					gl::DrawElementsIndirect(mode.to_gl(), index_type, offset);
+				}
+			}
+			self.disable_primitive_restart();
 		}
+		else {
+			unsafe {
+				if multi {
+					gl::MultiDrawArraysIndirect(mode.to_gl(), offset, range.len() as gl::types::GLsizei, 0);
+				}
+				else {
+					gl::DrawArraysIndirect(mode.to_gl(), offset);
+				}
+			}
+		}
+		unsafe {gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);}
+		self.unbind_texture();
 		Ok(())
 	}
 
-	fn draw_indexed_buffer(&self, indices : &AnyBuffer, mode : DrawMode) -> () {
+	// runs "draw" while capturing the output of the vertex processing stages into "buffer"
+	// "buffer" is bound to feedback binding index 0, "mode" must match the draw calls issued in "draw"
+	// returns the number of primitives written; "buffer.n_elems" is set to the number of vertices written
+	// fails for programs capturing several varyings in "FeedbackMode::Separate", which need one buffer each
+	#[track_caller]
+	pub fn capture_into<T, F>(&self, buffer : &mut Buffer<T>, mode : DrawMode, draw : F) -> Result<usize, GLError>
+	where F : FnOnce() -> Result<(), GLError>
+	{
+		if self.feedback_buffers > 1 {
+			return Err(GLError::FeedbackNeedsSeveralBuffers(self.feedback_buffers));
+		}
+		let (primitive_mode, vertices_per_primitive) = mode.to_feedback_primitive();
+
+		let query = Query::new(QueryKind::FeedbackPrimitivesWritten)?;
+
+		self.set_current();
+		unsafe {
+			gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, 0, buffer.id().0);
+			gl::BeginTransformFeedback(primitive_mode);
+		}
+		// feedback ends even if the query or the draw calls failed
+		let draw_result = query.measure(draw).and_then(|result| result);
+		unsafe {
+			gl::EndTransformFeedback();
+			gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, 0, 0);
+		}
+		draw_result?;
+		check("Program::capture_into")?;
+
+		let n_primitives = query.result() as usize;
+		buffer.set_len(n_primitives * vertices_per_primitive);
+		Ok(n_primitives)
+	}
+
+	#[inline]
+	fn check_range(from : usize, how_many : usize, n_elems : usize) -> Result<(), GLError> {
+		match from.checked_add(how_many) {
+			Some(end) if end <= n_elems => Ok(()),
+			_                           => Err(GLError::DrawRangeOutOfBounds),
+		}
+	}
+
+	#[inline]
+	fn enable_primitive_restart(&self) {
+		if let Some(restart_index) = self.primitive_restart {
+			unsafe {
+				gl::Enable(gl::PRIMITIVE_RESTART);
+				gl::PrimitiveRestartIndex(restart_index);
+			}
+		}
+	}
+
+	#[inline]
+	fn disable_primitive_restart(&self) {
+		if self.primitive_restart.is_some() {
+			unsafe {gl::Disable(gl::PRIMITIVE_RESTART);}
+		}
+	}
+
+	fn draw_indexed_buffer(&self, indices : &AnyBuffer, from : usize, how_many : usize, base_vertex : Option<GLint>, mode : &DrawMode) {
+		let offset = (from * indices.gpu_info.gl_type.size()) as *const std::os::raw::c_void;
 		self.bind_texture();
 		unsafe {gl::BindVertexArray(self.vao.0);}
 		unsafe {gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, indices.id().0);}
-		unsafe {
-			gl::DrawElements(
-				mode.to_gl(), 
-				indices.n_elems as gl::types::GLsizei, 
-				indices.gpu_info.gl_type.to_opengl_sym(), 
-				std::ptr::null()
-				);
+		self.enable_primitive_restart();
+		match base_vertex {
+			Some(base_vertex) => unsafe {
+				gl::DrawElementsBaseVertex(
+					mode.to_gl(), 
+					how_many as gl::types::GLsizei, 
+					indices.gpu_info.gl_type.to_opengl_sym(), 
+					offset,
+					base_vertex,
+					);
+			},
+			None => unsafe {
+				gl::DrawElements(
+					mode.to_gl(), 
+					how_many as gl::types::GLsizei, 
+					indices.gpu_info.gl_type.to_opengl_sym(), 
+					offset,
+					);
+			},
 		}
+		self.disable_primitive_restart();
 		self.unbind_texture();
 	}
 
-	fn draw_buffer_partial(&self, from : usize, how_many : usize, mode : DrawMode) -> () {
+	fn draw_array_buffer(&self, from : usize, how_many : usize, mode : &DrawMode) {
 		self.bind_texture();
 		unsafe {gl::BindVertexArray(self.vao.0);}
 		unsafe {gl::DrawArrays(mode.to_gl(), from as gl::types::GLint, how_many as gl::types::GLsizei);}
 		self.unbind_texture();
 	}
 
-	pub fn draw_buffer_partial_multi<A>(&self, ranges : &[(usize, usize)], mode : DrawMode) -> () {
-		self.bind_texture();
-		let starts = ranges.iter().map(|(x, _)| *x as gl::types::GLint).collect::<Vec<_>>();
-		let counts = ranges.iter().map(|(_, y)| *y as gl::types::GLsizei).collect::<Vec<_>>();
-		unsafe {gl::BindVertexArray(self.vao.0);}
+
+}
+
+// -- Uniform transfer between programs
+
+const MAX_UNIFORM_NAME_LENGTH : usize = 256;
+
+fn active_name(buffer : &[GLchar], length : GLsizei) -> String {
+	let bytes : Vec<u8> = buffer[.. length.max(0) as usize].iter().map(|c| *c as u8).collect();
+	String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum UniformStorage {
+	Float(usize),
+	Int(usize),
+	Uint(usize),
+	Matrix(usize),
+}
+
+impl UniformStorage {
+	fn from_gl(gl_type : GLenum) -> Option<Self> {
+		match gl_type {
+			gl::FLOAT                          => Some(Self::Float(1)),
+			gl::FLOAT_VEC2                     => Some(Self::Float(2)),
+			gl::FLOAT_VEC3                     => Some(Self::Float(3)),
+			gl::FLOAT_VEC4                     => Some(Self::Float(4)),
+			gl::INT          | gl::BOOL        => Some(Self::Int(1)),
+			gl::INT_VEC2     | gl::BOOL_VEC2   => Some(Self::Int(2)),
+			gl::INT_VEC3     | gl::BOOL_VEC3   => Some(Self::Int(3)),
+			gl::INT_VEC4     | gl::BOOL_VEC4   => Some(Self::Int(4)),
+			gl::UNSIGNED_INT                   => Some(Self::Uint(1)),
+			gl::UNSIGNED_INT_VEC2              => Some(Self::Uint(2)),
+			gl::UNSIGNED_INT_VEC3              => Some(Self::Uint(3)),
+			gl::UNSIGNED_INT_VEC4              => Some(Self::Uint(4)),
+			gl::FLOAT_MAT2                     => Some(Self::Matrix(2)),
+			gl::FLOAT_MAT3                     => Some(Self::Matrix(3)),
+			gl::FLOAT_MAT4                     => Some(Self::Matrix(4)),
+			gl_type if is_sampler(gl_type)     => Some(Self::Int(1)),
+			_ => None,
+		}
+	}
+}
+
+// uniforms in default block only, blocks are handled by "copy_uniform_block_bindings"
+fn copy_uniforms(from : ProgramId, to : ProgramId) {
+	let mut n_uniforms : GLint = 0;
+	unsafe {gl::GetProgramiv(from.0, gl::ACTIVE_UNIFORMS, &mut n_uniforms);}
+	unsafe {gl::UseProgram(to.0);}
+
+	let mut name_buffer : [GLchar; MAX_UNIFORM_NAME_LENGTH] = [0; MAX_UNIFORM_NAME_LENGTH];
+	for i in 0 .. n_uniforms.max(0) as GLuint {
+		let (mut length, mut size, mut gl_type) = (0, 0, 0);
 		unsafe {
-			gl::MultiDrawArrays(
-				mode.to_gl(), 
-				starts.as_ptr(), 
-				counts.as_ptr(), 
-				ranges.len() as gl::types::GLsizei
-				);
+			gl::GetActiveUniform(
+				from.0, i, MAX_UNIFORM_NAME_LENGTH as GLsizei,
+				&mut length, &mut size, &mut gl_type,
+				name_buffer.as_mut_ptr(),
+			);
+		}
+		let Some(storage) = UniformStorage::from_gl(gl_type) else { continue };
+		let name = active_name(&name_buffer, length);
+		// arrays are reported as "name[0]"
+		let base_name = name.strip_suffix("[0]").unwrap_or(&name);
+
+		for element in 0 .. size.max(1) {
+			let element_name = 
+				if size > 1 { format!("{}[{}]", base_name, element) }
+				else        { name.clone() };
+			let element_name = CString::new(element_name).unwrap();
+			let from_location = unsafe {gl::GetUniformLocation(from.0, element_name.as_ptr())};
+			let to_location   = unsafe {gl::GetUniformLocation(to.0,   element_name.as_ptr())};
+			if from_location == -1 || to_location == -1 {
+				continue;
+			}
+			copy_uniform_value(from, from_location, to_location, storage);
+		}
+	}
+}
+
+// "to" must be the current program
+fn copy_uniform_value(from : ProgramId, from_location : GLint, to_location : GLint, storage : UniformStorage) {
+	let mut floats = [0f32; 16];
+	let mut ints   = [0i32; 4];
+	let mut uints  = [0u32; 4];
+	unsafe {
+		match storage {
+			UniformStorage::Float(n) => {
+				gl::GetUniformfv(from.0, from_location, floats.as_mut_ptr());
+				match n {
+					1 => gl::Uniform1fv(to_location, 1, floats.as_ptr()),
+					2 => gl::Uniform2fv(to_location, 1, floats.as_ptr()),
+					3 => gl::Uniform3fv(to_location, 1, floats.as_ptr()),
+					_ => gl::Uniform4fv(to_location, 1, floats.as_ptr()),
+				}
+			},
+			UniformStorage::Int(n) => {
+				gl::GetUniformiv(from.0, from_location, ints.as_mut_ptr());
+				match n {
+					1 => gl::Uniform1iv(to_location, 1, ints.as_ptr()),
+					2 => gl::Uniform2iv(to_location, 1, ints.as_ptr()),
+					3 => gl::Uniform3iv(to_location, 1, ints.as_ptr()),
+					_ => gl::Uniform4iv(to_location, 1, ints.as_ptr()),
+				}
+			},
+			UniformStorage::Uint(n) => {
+				gl::GetUniformuiv(from.0, from_location, uints.as_mut_ptr());
+				match n {
+					1 => gl::Uniform1uiv(to_location, 1, uints.as_ptr()),
+					2 => gl::Uniform2uiv(to_location, 1, uints.as_ptr()),
+					3 => gl::Uniform3uiv(to_location, 1, uints.as_ptr()),
+					_ => gl::Uniform4uiv(to_location, 1, uints.as_ptr()),
+				}
+			},
+			// GetUniformfv returns matrices column-major
+			UniformStorage::Matrix(n) => {
+				gl::GetUniformfv(from.0, from_location, floats.as_mut_ptr());
+				match n {
+					2 => gl::UniformMatrix2fv(to_location, 1, gl::FALSE, floats.as_ptr()),
+					3 => gl::UniformMatrix3fv(to_location, 1, gl::FALSE, floats.as_ptr()),
+					_ => gl::UniformMatrix4fv(to_location, 1, gl::FALSE, floats.as_ptr()),
+				}
+			},
 		}
-		self.unbind_texture();
 	}
+}
 
+fn copy_uniform_block_bindings(from : ProgramId, to : ProgramId) {
+	let mut n_blocks : GLint = 0;
+	unsafe {gl::GetProgramiv(from.0, gl::ACTIVE_UNIFORM_BLOCKS, &mut n_blocks);}
 
+	let mut name_buffer : [GLchar; MAX_UNIFORM_NAME_LENGTH] = [0; MAX_UNIFORM_NAME_LENGTH];
+	for i in 0 .. n_blocks.max(0) as GLuint {
+		let mut length  = 0;
+		let mut binding = 0;
+		unsafe {
+			gl::GetActiveUniformBlockName(from.0, i, MAX_UNIFORM_NAME_LENGTH as GLsizei, &mut length, name_buffer.as_mut_ptr());
+			gl::GetActiveUniformBlockiv(from.0, i, gl::UNIFORM_BLOCK_BINDING, &mut binding);
+		}
+		let name = CString::new(active_name(&name_buffer, length)).unwrap();
+		let to_index = unsafe {gl::GetUniformBlockIndex(to.0, name.as_ptr())};
+		if to_index != gl::INVALID_INDEX {
+			unsafe {gl::UniformBlockBinding(to.0, to_index, binding as GLuint);}
+		}
+	}
 }
 
+
 #[derive(Debug)]
 pub enum DrawMode {
 	Tris,
@@ -440,4 +1124,183 @@ impl DrawMode {
 			DrawMode::TriStrip       => gl::TRIANGLE_STRIP,
 		}
 	}
+
+	// primitive type captured by transform feedback & number of vertices it is made of
+	fn to_feedback_primitive(&self) -> (GLenum, usize) {
+		match self {
+			DrawMode::Tris     | DrawMode::TriStrip  => (gl::TRIANGLES, 3),
+			DrawMode::Lines    | DrawMode::LineStrip => (gl::LINES,     2),
+			DrawMode::Points                         => (gl::POINTS,    1),
+		}
+	}
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub enum FeedbackMode {
+	Interleaved,
+	Separate,
+}
+
+impl FeedbackMode {
+	fn to_gl(self) -> GLenum {
+		match self {
+			FeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
+			FeedbackMode::Separate    => gl::SEPARATE_ATTRIBS,
+		}
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::trace::*;
+
+	#[test]
+	fn draw_buffer_issues_draw_elements_with_the_index_type() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let vert = Shader::<Vertex>::from_str("void main() {}").unwrap();
+		let frag = Shader::<Fragment>::from_str("void main() {}").unwrap();
+		let mut program = ProgramBuilder::new(vert, frag).attributes(&["position"]).build().unwrap();
+		program.set_indices(BufferBld::indices().data(&[0u16, 1, 2, 2, 1, 3]).unwrap());
+		trace.clear();
+
+		program.draw_buffer_partial(3, 3, DrawMode::Tris).unwrap();
+
+		let draws = trace.named("DrawElements");
+		assert_eq!(draws.len(), 1);
+		assert_eq!(draws[0].args, vec![
+			Value::of(gl::TRIANGLES),
+			Value::of(3),
+			Value::of(gl::UNSIGNED_SHORT),
+			// offset of the 4th index, in bytes
+			Value::Ptr(6),
+		]);
+	}
+
+	#[test]
+	fn feedback_varyings_are_checked() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let shaders = || (
+			Shader::<Vertex>::from_str("void main() {}").unwrap(),
+			Shader::<Fragment>::from_str("void main() {}").unwrap(),
+		);
+
+		let (vert, frag) = shaders();
+		trace.clear();
+		let nul = ProgramBuilder::new(vert, frag).feedback_varyings(&["out\0put"], FeedbackMode::Interleaved).build();
+		assert!(matches!(nul, Err(GLError::NameContainsNul(_))));
+		assert!(trace.named("CreateProgram").is_empty());
+
+		let (vert, frag) = shaders();
+		let program = ProgramBuilder::new(vert, frag)
+			.attributes(&[])
+			.feedback_varyings(&["position", "velocity"], FeedbackMode::Separate)
+			.build()
+			.unwrap();
+		let mut buffer = BufferBld::transform_feedback().allocate::<f32>(16).unwrap();
+		trace.clear();
+		let captured = program.capture_into(&mut buffer, DrawMode::Points, || Ok(()));
+		assert!(matches!(captured, Err(GLError::FeedbackNeedsSeveralBuffers(2))));
+		assert!(trace.named("BeginTransformFeedback").is_empty());
+	}
+
+	// glGetError reports an error once, after the program is linked
+	struct FailingLink(Recorder, bool);
+
+	impl Backend for FailingLink {
+		fn call(&mut self, call : &Call) -> Value {
+			let value = self.0.call(call);
+			match call.name {
+				"LinkProgram"        => { self.1 = true; value },
+				"GetError" if self.1 => { self.1 = false; Value::of(gl::INVALID_OPERATION) },
+				_                    => value,
+			}
+		}
+	}
+
+	#[test]
+	fn failed_programs_are_deleted() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let mut gl   = GLWrap::with_backend(FailingLink(recorder, false));
+		gl.set_error_checking(true);
+
+		let vert = Shader::<Vertex>::from_str("void main() {}").unwrap();
+		let frag = Shader::<Fragment>::from_str("void main() {}").unwrap();
+		let program = ProgramBuilder::new(vert, frag).attributes(&[]).build();
+		assert!(matches!(program, Err(GLError::Driver { code : gl::INVALID_OPERATION, .. })));
+		let deleted = trace.named("DeleteProgram");
+		assert_eq!(deleted.len(), 1);
+		assert_eq!(deleted[0].args[0], trace.named("AttachShader")[0].args[0]);
+	}
+
+	// a program linked with two separate feedback varyings
+	struct SeparateFeedback(Recorder);
+
+	impl Backend for SeparateFeedback {
+		fn call(&mut self, call : &Call) -> Value {
+			let value = self.0.call(call);
+			match (call.name, call.args.get(1).map(|pname| pname.as_int() as GLenum)) {
+				("GetProgramiv", Some(gl::TRANSFORM_FEEDBACK_BUFFER_MODE)) => Value::of(gl::SEPARATE_ATTRIBS),
+				("GetProgramiv", Some(gl::TRANSFORM_FEEDBACK_VARYINGS))    => Value::of(2),
+				_ => value,
+			}
+		}
+	}
+
+	#[test]
+	fn binaries_keep_their_feedback_buffers() {
+		let recorder = Recorder::new().with_integer(gl::MAJOR_VERSION, 4).with_integer(gl::MINOR_VERSION, 1);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(SeparateFeedback(recorder));
+
+		let program = Program::from_binary(0, &[0; 4], PipelineStages::VERTEX | PipelineStages::FRAGMENT, false, Some(vec![])).unwrap();
+		let mut buffer = BufferBld::transform_feedback().allocate::<f32>(16).unwrap();
+		trace.clear();
+		let captured = program.capture_into(&mut buffer, DrawMode::Points, || Ok(()));
+		assert!(matches!(captured, Err(GLError::FeedbackNeedsSeveralBuffers(2))));
+		assert!(trace.named("BeginTransformFeedback").is_empty());
+	}
+
+	#[test]
+	fn draw_indirect_rejects_out_of_range_indices() {
+		// GL 4.0 has glDrawArraysIndirect, not glMultiDrawArraysIndirect
+		let recorder = Recorder::new().with_integer(gl::MAJOR_VERSION, 4).with_integer(gl::MINOR_VERSION, 0);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let vert = Shader::<Vertex>::from_str("void main() {}").unwrap();
+		let frag = Shader::<Fragment>::from_str("void main() {}").unwrap();
+		let program  = ProgramBuilder::new(vert, frag).attributes(&[]).build().unwrap();
+		let command  = DrawArraysIndirectCommand { count : 3, instance_count : 1, first : 0, base_instance : 0 };
+		let commands = BufferBld::draw_indirect().data(&[command; 2]).unwrap();
+		trace.clear();
+
+		program.draw_indirect(&commands, 1, DrawMode::Tris).unwrap();
+		let draws = trace.named("DrawArraysIndirect");
+		assert_eq!(draws.len(), 1);
+		// offset of the 2nd command, in bytes
+		assert_eq!(draws[0].args, vec![Value::of(gl::TRIANGLES), Value::Ptr(16)]);
+		assert!(trace.named("MultiDrawArraysIndirect").is_empty());
+		let multi = program.multi_draw_indirect(&commands, 0 .. 2, DrawMode::Tris);
+		assert!(matches!(multi, Err(GLError::Unsupported(Feature::MultiDrawIndirect))));
+
+		for index in [2, usize::MAX] {
+			let drawn = program.draw_indirect(&commands, index, DrawMode::Tris);
+			assert!(matches!(drawn, Err(GLError::DrawRangeOutOfBounds)));
+		}
+	}
 }
@@ -0,0 +1,168 @@
+use gl::types::*;
+
+use crate::capabilities::{self, Feature};
+use crate::error::*;
+
+
+
+// -- QUERIES
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct QueryId(pub GLuint);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QueryKind {
+	TimeElapsed,
+	Timestamp,
+	SamplesPassed,
+	AnySamplesPassed,
+	PrimitivesGenerated,
+	FeedbackPrimitivesWritten,
+}
+
+impl QueryKind {
+	pub fn cst(&self) -> GLenum {
+		match self {
+			Self::TimeElapsed               => gl::TIME_ELAPSED,
+			Self::Timestamp                 => gl::TIMESTAMP,
+			Self::SamplesPassed             => gl::SAMPLES_PASSED,
+			Self::AnySamplesPassed          => gl::ANY_SAMPLES_PASSED,
+			Self::PrimitivesGenerated       => gl::PRIMITIVES_GENERATED,
+			Self::FeedbackPrimitivesWritten => gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN,
+		}
+	}
+
+	pub fn is_occlusion(&self) -> bool {
+		matches!(self, Self::SamplesPassed | Self::AnySamplesPassed)
+	}
+}
+
+
+// what the GPU does when the result of an occlusion query is not available yet
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConditionalMode {
+	Wait,
+	NoWait,
+	ByRegionWait,
+	ByRegionNoWait,
+}
+
+impl ConditionalMode {
+	pub fn cst(&self) -> GLenum {
+		match self {
+			Self::Wait           => gl::QUERY_WAIT,
+			Self::NoWait         => gl::QUERY_NO_WAIT,
+			Self::ByRegionWait   => gl::QUERY_BY_REGION_WAIT,
+			Self::ByRegionNoWait => gl::QUERY_BY_REGION_NO_WAIT,
+		}
+	}
+}
+
+
+#[derive(Debug)]
+pub struct Query {
+	pub id   : QueryId,
+	pub kind : QueryKind,
+}
+
+impl Query {
+	#[track_caller]
+	pub fn new(kind : QueryKind) -> Result<Self, GLError> {
+		if matches!(kind, QueryKind::TimeElapsed | QueryKind::Timestamp) {
+			capabilities::require(Feature::TimerQueries)?;
+		}
+		let mut id = 0;
+		unsafe {gl::GenQueries(1, &mut id);}
+		if id == 0 {
+			return Err(GLError::CouldNotCreateQuery);
+		}
+		let query = Self { id: QueryId(id), kind };
+		check("Query::new")?;
+		Ok(query)
+	}
+
+	// timestamp queries are not delimited, use "record" instead
+	#[track_caller]
+	pub fn begin(&self) -> Result<(), GLError> {
+		if self.kind == QueryKind::Timestamp {
+			return Err(GLError::WrongQueryKind);
+		}
+		unsafe {gl::BeginQuery(self.kind.cst(), self.id.0);}
+		check("Query::begin")
+	}
+
+	#[track_caller]
+	pub fn end(&self) -> Result<(), GLError> {
+		if self.kind == QueryKind::Timestamp {
+			return Err(GLError::WrongQueryKind);
+		}
+		unsafe {gl::EndQuery(self.kind.cst());}
+		check("Query::end")
+	}
+
+	// records the GPU time once all previous commands have completed
+	#[track_caller]
+	pub fn record(&self) -> Result<(), GLError> {
+		if self.kind != QueryKind::Timestamp {
+			return Err(GLError::WrongQueryKind);
+		}
+		unsafe {gl::QueryCounter(self.id.0, gl::TIMESTAMP);}
+		check("Query::record")
+	}
+
+	#[track_caller]
+	pub fn measure<F, R>(&self, f : F) -> Result<R, GLError>
+	where F : FnOnce() -> R
+	{
+		self.begin()?;
+		let result = f();
+		self.end()?;
+		Ok(result)
+	}
+
+	pub fn is_available(&self) -> bool {
+		let mut available : GLint = 0;
+		unsafe {gl::GetQueryObjectiv(self.id.0, gl::QUERY_RESULT_AVAILABLE, &mut available);}
+		available != 0
+	}
+
+	// does not stall the pipeline: returns None if the GPU isn't done yet
+	pub fn try_result(&self) -> Option<u64> {
+		if self.is_available() {
+			Some(self.result())
+		}
+		else {
+			None
+		}
+	}
+
+	// blocks until the result is available
+	// nanoseconds for time queries, a count for the others
+	pub fn result(&self) -> u64 {
+		let mut result : GLuint64 = 0;
+		unsafe {gl::GetQueryObjectui64v(self.id.0, gl::QUERY_RESULT, &mut result);}
+		result
+	}
+
+	// draw calls issued by "draw" are discarded by the GPU if no sample passed this occlusion query
+	#[track_caller]
+	pub fn conditional<F, R>(&self, mode : ConditionalMode, draw : F) -> Result<R, GLError>
+	where F : FnOnce() -> R
+	{
+		if !self.kind.is_occlusion() {
+			return Err(GLError::WrongQueryKind);
+		}
+		unsafe {gl::BeginConditionalRender(self.id.0, mode.cst());}
+		check("Query::conditional")?;
+		let result = draw();
+		unsafe {gl::EndConditionalRender();}
+		check("Query::conditional")?;
+		Ok(result)
+	}
+}
+
+impl Drop for Query {
+	fn drop(&mut self) {
+		unsafe {gl::DeleteQueries(1, &self.id.0);}
+	}
+}
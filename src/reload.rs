@@ -0,0 +1,321 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::error::*;
+use crate::preprocess::*;
+use crate::program::*;
+use crate::shader::*;
+
+
+
+// -- SHADER WATCHER
+// polls the modification time of files, no platform-specific notification needed
+
+#[derive(Debug, Default)]
+pub struct ShaderWatcher {
+	files : Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ShaderWatcher {
+	pub fn new<I, P>(files : I) -> Self
+	where I : IntoIterator<Item = P>, P : Into<PathBuf>
+	{
+		let mut to_return = Self::default();
+		to_return.set_files(files);
+		to_return
+	}
+
+	// modification times are recorded now: only later changes are reported
+	pub fn set_files<I, P>(&mut self, files : I)
+	where I : IntoIterator<Item = P>, P : Into<PathBuf>
+	{
+		self.files = files
+			.into_iter()
+			.map(|file| {
+				let file = file.into();
+				let modified = modification_time(&file);
+				(file, modified)
+			})
+			.collect();
+	}
+
+	// whether any file was modified, created or deleted since the last poll
+	pub fn poll(&mut self) -> bool {
+		let mut changed = false;
+		for (file, last_modified) in self.files.iter_mut() {
+			let modified = modification_time(file);
+			if modified != *last_modified {
+				*last_modified = modified;
+				changed = true;
+			}
+		}
+		changed
+	}
+}
+
+fn modification_time(file : &PathBuf) -> Option<SystemTime> {
+	std::fs::metadata(file).and_then(|metadata| metadata.modified()).ok()
+}
+
+
+
+// -- RELOADABLE PROGRAM
+// a program rebuilt from its source files whenever they (or the files they include) change
+// attribute bindings, textures, indices and uniform values carry over to the new version
+
+#[derive(Debug)]
+pub enum ReloadStatus {
+	Unchanged,
+	Reloaded,
+	// the previous version is still in use
+	Failed(GLError),
+}
+
+pub struct ReloadableProgram {
+	vert_path    : String,
+	frag_path    : String,
+	geom_path    : Option<String>,
+	preprocessor : Preprocessor,
+	configure    : Box<dyn Fn(ProgramBuilder) -> ProgramBuilder>,
+	program      : Program,
+	watcher      : ShaderWatcher,
+}
+
+impl ReloadableProgram {
+	pub fn new(vert_path : &str, frag_path : &str) -> Result<Self, GLError> {
+		Self::with_options(vert_path, frag_path, None, Preprocessor::new(), |builder| builder)
+	}
+
+	// "configure" is applied to the builder of every version, e.g. to declare attributes
+	pub fn with_options<F>(
+		vert_path    : &str,
+		frag_path    : &str,
+		geom_path    : Option<&str>,
+		preprocessor : Preprocessor,
+		configure    : F,
+	) -> Result<Self, GLError>
+	where F : Fn(ProgramBuilder) -> ProgramBuilder + 'static
+	{
+		let mut files = Vec::new();
+		let program = build(vert_path, frag_path, geom_path, &preprocessor, &configure, &mut files)?;
+		Ok(Self {
+			vert_path    : vert_path.to_string(),
+			frag_path    : frag_path.to_string(),
+			geom_path    : geom_path.map(str::to_string),
+			preprocessor,
+			configure    : Box::new(configure),
+			program,
+			watcher      : ShaderWatcher::new(files),
+		})
+	}
+
+	// uniforms & duplicates obtained from the program must not be kept across calls to "poll"
+	#[inline]
+	pub fn program(&self) -> &Program { &self.program }
+
+	#[inline]
+	pub fn program_mut(&mut self) -> &mut Program { &mut self.program }
+
+	// to be called e.g. once per frame
+	pub fn poll(&mut self) -> ReloadStatus {
+		if !self.watcher.poll() {
+			return ReloadStatus::Unchanged;
+		}
+
+		let mut files = Vec::new();
+		let result = build(
+			&self.vert_path, &self.frag_path, self.geom_path.as_deref(),
+			&self.preprocessor, &self.configure, &mut files,
+		);
+		// even if the build failed: a fix may be in a file that wasn't watched yet
+		self.watcher.set_files(files);
+		match result {
+			Ok(mut program) => {
+				program.restore_state_from(&self.program);
+				let old_program = std::mem::replace(&mut self.program, program);
+				old_program.delete();
+				ReloadStatus::Reloaded
+			},
+			Err(error) => ReloadStatus::Failed(error),
+		}
+	}
+}
+
+// "files" receives every file the program is made of, even if the build fails midway
+fn build(
+	vert_path    : &str,
+	frag_path    : &str,
+	geom_path    : Option<&str>,
+	preprocessor : &Preprocessor,
+	configure    : &dyn Fn(ProgramBuilder) -> ProgramBuilder,
+	files        : &mut Vec<String>,
+) -> Result<Program, GLError>
+{
+	files.push(vert_path.to_string());
+	files.push(frag_path.to_string());
+	files.extend(geom_path.map(str::to_string));
+
+	let mut preprocess = |path : &str| -> Result<Preprocessed, GLError> {
+		let preprocessed = preprocessor.process_file(path)?;
+		files.extend(preprocessed.files.iter().skip(1).cloned());
+		Ok(preprocessed)
+	};
+	let vert = preprocess(vert_path)?;
+	let frag = preprocess(frag_path)?;
+	let geom = geom_path.map(&mut preprocess).transpose()?;
+
+	let mut builder = ProgramBuilder::new(
+		Shader::<Vertex>::from_preprocessed(&vert)?,
+		Shader::<Fragment>::from_preprocessed(&frag)?,
+	);
+	if let Some(geom) = geom {
+		builder = builder.geom_shader(Shader::<Geometry>::from_preprocessed(&geom)?);
+	}
+	configure(builder).build()
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::buffer::*;
+	use crate::math3d::*;
+	use crate::texture::*;
+	use crate::trace::*;
+	use std::time::Duration;
+
+	// a fresh directory per test, as tests run in parallel
+	fn directory(test : &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("migl-reload-{}-{}", std::process::id(), test));
+		std::fs::create_dir_all(&directory).unwrap();
+		directory
+	}
+
+	// explicit, as the resolution of modification times may be coarser than a test
+	fn touch(file : &PathBuf, modified : SystemTime) {
+		std::fs::File::options().write(true).open(file).unwrap().set_modified(modified).unwrap();
+	}
+
+	#[test]
+	fn untouched_files_are_not_reported() {
+		let directory = directory("untouched");
+		let file = directory.join("shader.glsl");
+		std::fs::write(&file, "void main() {}").unwrap();
+
+		let mut watcher = ShaderWatcher::new([&file]);
+		assert!(!watcher.poll());
+		assert!(!watcher.poll());
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn modified_files_are_reported_once() {
+		let directory = directory("modified");
+		let file = directory.join("shader.glsl");
+		std::fs::write(&file, "void main() {}").unwrap();
+		let mut watcher = ShaderWatcher::new([&file]);
+
+		touch(&file, SystemTime::now() + Duration::from_secs(10));
+		assert!(watcher.poll());
+		assert!(!watcher.poll());
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn missing_files_are_reported_when_created_or_deleted() {
+		let directory = directory("missing");
+		let file = directory.join("shader.glsl");
+		let mut watcher = ShaderWatcher::new([&file]);
+		assert!(!watcher.poll());
+
+		std::fs::write(&file, "void main() {}").unwrap();
+		assert!(watcher.poll());
+		assert!(!watcher.poll());
+
+		std::fs::remove_file(&file).unwrap();
+		assert!(watcher.poll());
+		assert!(!watcher.poll());
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	fn path(file : &std::path::Path) -> &str { file.to_str().unwrap() }
+
+	#[test]
+	fn failed_builds_keep_the_old_program_and_watch_new_files() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let directory = directory("failed");
+		let (vert, frag, common) = (directory.join("vert.glsl"), directory.join("frag.glsl"), directory.join("common.glsl"));
+		std::fs::write(&vert, "void main() {}").unwrap();
+		std::fs::write(&frag, "void main() {}").unwrap();
+		let mut reloadable = ReloadableProgram::new(path(&vert), path(&frag)).unwrap();
+		let id = reloadable.program().id;
+
+		// "common.glsl" is found, "missing.glsl" isn't
+		std::fs::write(&common, "float scale() { return 1.0; }").unwrap();
+		std::fs::write(&vert, "#include \"common.glsl\"\nvoid main() {}").unwrap();
+		std::fs::write(&frag, "#include \"missing.glsl\"\nvoid main() {}").unwrap();
+		touch(&vert, SystemTime::now() + Duration::from_secs(10));
+		trace.clear();
+		assert!(matches!(reloadable.poll(), ReloadStatus::Failed(GLError::IncludeNotFound(_))));
+		assert_eq!(reloadable.program().id, id);
+		assert!(trace.named("DeleteProgram").is_empty());
+
+		touch(&common, SystemTime::now() + Duration::from_secs(20));
+		assert!(matches!(reloadable.poll(), ReloadStatus::Failed(_)));
+		assert!(matches!(reloadable.poll(), ReloadStatus::Unchanged));
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn reloads_carry_the_state_over_and_free_the_shaders() {
+		let recorder = Recorder::new().with_uniform("tex", gl::SAMPLER_2D).with_uniform("scale", gl::FLOAT);
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let directory = directory("reloaded");
+		let (vert, frag) = (directory.join("vert.glsl"), directory.join("frag.glsl"));
+		std::fs::write(&vert, "void main() {}").unwrap();
+		std::fs::write(&frag, "void main() {}").unwrap();
+		let mut reloadable = ReloadableProgram::with_options(
+			path(&vert), path(&frag), None, Preprocessor::new(),
+			|builder| builder.attributes(&["position"]),
+		).unwrap();
+
+		let positions = BufferBld::array().data(&[V3::new([0., 0., 0.]); 3]).unwrap();
+		let texture   = Texture::new(&image::DynamicImage::new_rgba8(1, 1)).unwrap();
+		let texture_id = texture.id.0;
+		let old_id = reloadable.program().id;
+		reloadable.program().bind("position", positions.direct_view().unwrap()).unwrap();
+		reloadable.program_mut().texture("tex", texture).unwrap();
+
+		touch(&frag, SystemTime::now() + Duration::from_secs(10));
+		trace.clear();
+		assert!(matches!(reloadable.poll(), ReloadStatus::Reloaded));
+		let new_id = reloadable.program().id;
+		assert_ne!(new_id, old_id);
+		assert_eq!(trace.named("DeleteProgram")[0].args, vec![Value::of(old_id.0)]);
+		// the shaders of the new version are freed once linked
+		assert_eq!(trace.named("DeleteShader").len(), 2);
+
+		// both uniforms are read from the old version and written to the new one
+		let reads = trace.named("GetUniformiv").into_iter().chain(trace.named("GetUniformfv")).collect::<Vec<_>>();
+		assert!(reads.iter().all(|read| read.args[0] == Value::of(old_id.0)));
+		assert_eq!(reads.len(), 2);
+		assert_eq!(trace.named("Uniform1iv")[0].args[0 ..= 1], [Value::of(0), Value::of(1)]);
+		assert_eq!(trace.named("Uniform1fv")[0].args[0 ..= 1], [Value::of(1), Value::of(1)]);
+
+		trace.clear();
+		reloadable.program().draw_buffer(DrawMode::Tris).unwrap();
+		assert_eq!(trace.named("BindTexture")[0].args, vec![Value::of(gl::TEXTURE_2D), Value::of(texture_id)]);
+		assert_eq!(trace.named("DrawArrays")[0].args[2], Value::of(3));
+		std::fs::remove_dir_all(directory).unwrap();
+	}
+}
@@ -0,0 +1,128 @@
+use gl::types::*;
+
+use crate::capabilities;
+use crate::error::*;
+use crate::texture::TexFormat;
+use crate::debug;
+use crate::dsa;
+
+
+
+// -- RENDERBUFFERS
+// images that can only be rendered to, not sampled : the right choice for depth or stencil buffers
+// of offscreen passes, and for multisampled targets that are resolved by a blit
+// attached to a framebuffer with "FrameBufferBuilder", like textures
+
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RenderbufferId(pub GLuint);
+
+#[derive(Debug)]
+pub struct Renderbuffer {
+	pub id      : RenderbufferId,
+	pub width   : u32,
+	pub height  : u32,
+	pub format  : TexFormat,
+	// 0 when not multisampled
+	pub samples : u32,
+}
+
+impl Renderbuffer {
+	#[track_caller]
+	pub fn new(width : u32, height : u32, format : TexFormat) -> Result<Self, GLError> {
+		Self::new_multisampled(width, height, format, 0)
+	}
+
+	#[track_caller]
+	pub fn new_multisampled(width : u32, height : u32, format : TexFormat, samples : u32) -> Result<Self, GLError> {
+		let max_samples = capabilities::current().map(|capabilities| capabilities.max_samples);
+		if max_samples.is_some_and(|max_samples| samples as usize > max_samples) {
+			return Err(GLError::TooManySamples);
+		}
+
+		let id = dsa::create_renderbuffer();
+		if id == 0 {
+			return Err(GLError::CouldNotCreateRenderbuffer);
+		}
+		// dropped on error
+		let renderbuffer = Self { id : RenderbufferId(id), width, height, format, samples };
+
+		dsa::renderbuffer_storage(id, samples, format.to_sized_opengl_sym(), width, height);
+		check("Renderbuffer::new")?;
+
+		Ok(renderbuffer)
+	}
+
+	// reallocates the storage, the content is lost
+	// framebuffers it is attached to keep it, but should be resized alike to stay complete
+	#[track_caller]
+	pub fn resize(&mut self, width : u32, height : u32) -> Result<(), GLError> {
+		if (width, height) == (self.width, self.height) {
+			return Ok(());
+		}
+		dsa::renderbuffer_storage(self.id.0, self.samples, self.format.to_sized_opengl_sym(), width, height);
+		check("Renderbuffer::resize")?;
+
+		self.width  = width;
+		self.height = height;
+		Ok(())
+	}
+
+	pub fn is_multisampled(&self) -> bool {
+		self.samples > 0
+	}
+
+	// shown by debugging tools, see "debug"
+	pub fn set_label(&self, label : &str) {
+		debug::set_label(gl::RENDERBUFFER, self.id.0, label);
+	}
+}
+
+impl Drop for Renderbuffer {
+	fn drop(&mut self) {
+		unsafe {gl::DeleteRenderbuffers(1, &self.id.0);}
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::frame::FrameBufferBuilder;
+	use crate::texture::Texture;
+	use crate::trace::*;
+
+	#[test]
+	fn depth_renderbuffer_is_attached_resized_and_deleted() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(recorder);
+
+		let color     = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		let mut depth = Renderbuffer::new(4, 4, TexFormat::Depth).unwrap();
+		let _framebuffer = FrameBufferBuilder::new().attach_color(&color).attach_depth(&depth).build().unwrap();
+
+		let attachment = &trace.named("FramebufferRenderbuffer")[0];
+		assert_eq!(attachment.args[1], Value::of(gl::DEPTH_ATTACHMENT));
+		assert_eq!(attachment.args[3], Value::of(depth.id.0));
+		assert_eq!(trace.named("FramebufferTexture2D").len(), 1);
+
+		trace.clear();
+		depth.resize(8, 2).unwrap();
+		depth.resize(8, 2).unwrap();
+		let storage = trace.named("RenderbufferStorageMultisample");
+		assert_eq!(storage.len(), 1);
+		assert_eq!(storage[0].args[2..], [Value::of(gl::DEPTH_COMPONENT24), Value::of(8), Value::of(2)]);
+
+		drop(depth);
+		assert_eq!(trace.named("DeleteRenderbuffers").len(), 1);
+
+		let too_many = Renderbuffer::new_multisampled(4, 4, TexFormat::Rgba, 64);
+		assert!(matches!(too_many, Err(GLError::TooManySamples)));
+	}
+}
@@ -1,29 +1,30 @@
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
-
 use gl::types::*;
 
 use crate::log::*;
 use crate::error::*;
+use crate::preprocess::*;
+use crate::diagnostics::*;
 
 
 
 pub struct Vertex;
-impl ShaderKind for Vertex   {const GLENUM_VAL : gl::types::GLenum = gl::VERTEX_SHADER;}
+impl ShaderKind for Vertex   {const GLENUM_VAL : gl::types::GLenum = gl::VERTEX_SHADER;   const STAGE : Stage = Stage::Vertex;}
 pub struct Fragment;
-impl ShaderKind for Fragment {const GLENUM_VAL : gl::types::GLenum = gl::FRAGMENT_SHADER;}
+impl ShaderKind for Fragment {const GLENUM_VAL : gl::types::GLenum = gl::FRAGMENT_SHADER; const STAGE : Stage = Stage::Fragment;}
 pub struct Geometry;
-impl ShaderKind for Geometry {const GLENUM_VAL : gl::types::GLenum = gl::GEOMETRY_SHADER;}
+impl ShaderKind for Geometry {const GLENUM_VAL : gl::types::GLenum = gl::GEOMETRY_SHADER; const STAGE : Stage = Stage::Geometry;}
 
 
 pub trait ShaderKind {
 	const GLENUM_VAL : gl::types::GLenum;
+	const STAGE      : Stage;
 }
 
 #[derive(Debug)]
 pub struct Shader<K> {
 	pub id  : GLuint,
+	// diagnostics of a successful compilation
+	warnings : Vec<Diagnostic>,
 	phantom : std::marker::PhantomData<K>
 }
 
@@ -32,7 +33,32 @@ pub struct Shader<K> {
 
 impl<K : ShaderKind> Shader<K> {
 	
+	#[track_caller]
 	pub fn from_str(source : &str) -> Result<Self, GLError> {
+		Self::compile(source, None)
+	}
+
+
+	// the source goes through the default preprocessor, so that '#include's are resolved
+	#[track_caller]
+	pub fn from_file(filepath : &str) -> Result<Self, GLError> {
+		let preprocessed = Preprocessor::new().process_file(filepath)?;
+		Shader::<K>::from_preprocessed(&preprocessed)
+	}
+
+
+	// diagnostics point to the original files and lines
+	#[track_caller]
+	pub fn from_preprocessed(preprocessed : &Preprocessed) -> Result<Self, GLError> {
+		Self::compile(&preprocessed.source, Some(preprocessed))
+	}
+
+
+	pub fn warnings(&self) -> &[Diagnostic] { &self.warnings }
+
+
+	#[track_caller]
+	fn compile(source : &str, preprocessed : Option<&Preprocessed>) -> Result<Self, GLError> {
 		let id = unsafe { gl::CreateShader(K::GLENUM_VAL) };
 		let phantom = std::marker::PhantomData::<K>;
 
@@ -46,26 +72,17 @@ impl<K : ShaderKind> Shader<K> {
 
 
 		// -- CHECK IF ERROR HAS OCCURRED
-		match get_log(id, LogKind::CompilerLog) {
-			None            => Ok(Self {id, phantom}),
-			Some(error_msg) => Err(GLError::CompileError(error_msg)),
+		let (success, log) = get_status_and_log(id, LogKind::CompilerLog);
+		if success {
+			let warnings = parse_log(&log, K::STAGE, Severity::Warning, preprocessed);
+			let shader = Self {id, warnings, phantom};
+			check("Shader::compile")?;
+			Ok(shader)
+		}
+		else {
+			unsafe { gl::DeleteShader(id) }
+			Err(GLError::CompileError(parse_log(&log, K::STAGE, Severity::Error, preprocessed)))
 		}
-	}
-
-
-	pub fn from_file(filepath : &str) -> Result<Self, GLError> {
-		let path = Path::new(filepath);
-
-		// Open the path in read-only mode, returns `io::Result<File>`
-		let mut file = File::open(&path)?;
-
-
-		// // Read the file contents into a string, returns `io::Result<usize>`
-		let mut s = String::new();
-		file.read_to_string(&mut s)?;
-
-
-		Shader::<K>::from_str(&s)
 	}
 }
 
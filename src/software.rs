@@ -0,0 +1,480 @@
+use image::{DynamicImage, ImageBuffer, Rgb};
+
+use crate::error::*;
+use crate::math3d::*;
+use crate::program::DrawMode;
+
+
+
+// -- SOFTWARE RENDERER
+// a CPU rasterizer to render scenes without a driver (e.g. in CI) and as a reference to diff GL output
+// against, see the "software_cube" example
+// shaders are closures: the vertex shader maps a vertex to clip coordinates & varyings, the fragment
+// shader maps the interpolated varyings to a color ; uniforms & textures are captured by the closures
+//
+// "Buffer", "Texture", "Program" & "FrameBuffer" mirror their GL counterparts, not their signatures:
+// nothing is bound, draws take their target & vertices, textures are RGBA with no format, and
+// programs have no attributes, uniforms nor texture units ; only the drawing API is shared
+//
+// follows GL conventions: NDC depth in [-1, 1] stored in [0, 1], depth test LESS (see
+// "GLWrap::enable_depth"), texel row 0 at the bottom, pixels sampled at their center
+// no culling, no clipping: primitives with a vertex behind the camera (w <= 0) are dropped
+
+
+
+// -- VARYINGS
+
+// outputs of the vertex shader, interpolated across primitives
+pub trait Varying : Copy {
+	fn scaled(self, factor : f32) -> Self;
+	fn added(self, other : Self) -> Self;
+}
+
+impl Varying for () {
+	fn scaled(self, _ : f32) -> Self {}
+	fn added(self, _ : Self) -> Self {}
+}
+
+impl Varying for f32 {
+	fn scaled(self, factor : f32) -> Self { self * factor }
+	fn added(self, other : Self) -> Self { self + other }
+}
+
+impl<const N : usize> Varying for Point<N> {
+	fn scaled(self, factor : f32) -> Self { self.scale(factor) }
+	fn added(self, other : Self) -> Self { self + other }
+}
+
+macro_rules! tuple_varying {
+	($($name:ident : $index:tt),*) => {
+		impl<$($name : Varying),*> Varying for ($($name,)*) {
+			fn scaled(self, factor : f32) -> Self { ($(self.$index.scaled(factor),)*) }
+			fn added(self, other : Self) -> Self { ($(self.$index.added(other.$index),)*) }
+		}
+	};
+}
+tuple_varying!(A : 0, B : 1);
+tuple_varying!(A : 0, B : 1, C : 2);
+tuple_varying!(A : 0, B : 1, C : 2, D : 3);
+
+fn combine<O : Varying, const K : usize>(values : [O; K], weights : [f32; K]) -> O {
+	let mut result = values[0].scaled(weights[0]);
+	for i in 1 .. K {
+		result = result.added(values[i].scaled(weights[i]));
+	}
+	result
+}
+
+
+
+// -- BUFFERS
+
+#[derive(Debug, Clone)]
+pub struct Buffer<A> {
+	data : Vec<A>,
+}
+
+impl<A> Buffer<A> {
+	#[inline]
+	pub fn len(&self) -> usize { self.data.len() }
+
+	#[inline]
+	pub fn is_empty(&self) -> bool { self.data.is_empty() }
+
+	#[inline]
+	pub fn as_slice(&self) -> &[A] { &self.data }
+}
+
+impl<A : Copy> Buffer<A> {
+	pub fn from_data(data : &[A]) -> Self {
+		Self { data : data.to_vec() }
+	}
+
+	pub fn replace_data(&mut self, offset : usize, data : &[A]) -> Result<(), GLError> {
+		let end = offset.checked_add(data.len()).ok_or(GLError::BufferOverflow)?;
+		if end > self.data.len() {
+			return Err(GLError::BufferOverflow);
+		}
+		self.data[offset .. end].copy_from_slice(data);
+		Ok(())
+	}
+
+	pub fn extend(&mut self, data : &[A]) {
+		self.data.extend_from_slice(data);
+	}
+}
+
+
+
+// -- TEXTURES
+
+// RGBA, each channel in [0, 1]
+#[derive(Debug, Clone)]
+pub struct Texture {
+	pub width  : u32,
+	pub height : u32,
+	texels     : Vec<V4>,
+}
+
+impl Texture {
+	// transparent black
+	pub fn allocate(width : u32, height : u32) -> Self {
+		Self { width, height, texels : vec![V4::new([0.0; 4]); (width * height) as usize] }
+	}
+
+	// the first row of the image becomes texel row 0, as with "texture::Texture::new"
+	pub fn new(image : &DynamicImage) -> Self {
+		let image = image.to_rgba32f();
+		let texels = image.pixels().map(|pixel| V4::new(pixel.0)).collect();
+		Self { width : image.width(), height : image.height(), texels }
+	}
+
+	#[inline]
+	pub fn texel(&self, x : u32, y : u32) -> V4 {
+		self.texels[(y * self.width + x) as usize]
+	}
+
+	#[inline]
+	pub fn set_texel(&mut self, x : u32, y : u32, color : V4) {
+		self.texels[(y * self.width + x) as usize] = color;
+	}
+
+	// bilinear filtering, coordinates repeated on both axes
+	// transparent black for an empty texture
+	pub fn sample(&self, uv : V2) -> V4 {
+		if self.width == 0 || self.height == 0 {
+			return V4::new([0.0; 4]);
+		}
+		let x = uv.0[0] * self.width  as f32 - 0.5;
+		let y = uv.0[1] * self.height as f32 - 0.5;
+		let (x0, y0) = (x.floor(), y.floor());
+		let (fx, fy) = (x - x0, y - y0);
+
+		let texel = |dx : i64, dy : i64| {
+			let tx = (x0 as i64 + dx).rem_euclid(self.width  as i64) as u32;
+			let ty = (y0 as i64 + dy).rem_euclid(self.height as i64) as u32;
+			self.texel(tx, ty)
+		};
+		combine(
+			[texel(0, 0), texel(1, 0), texel(0, 1), texel(1, 1)],
+			[(1.0 - fx) * (1.0 - fy), fx * (1.0 - fy), (1.0 - fx) * fy, fx * fy],
+		)
+	}
+
+	// same layout as "texture::Texture::to_image" with "GLType::Ubyte"
+	pub fn to_image(&self) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+		ImageBuffer::from_fn(self.width, self.height, |x, y| {
+			let texel = self.texel(x, y);
+			Rgb([0, 1, 2].map(|i| (texel.0[i].clamp(0.0, 1.0) * 255.0).round() as u8))
+		})
+	}
+}
+
+
+
+// -- FRAMEBUFFERS
+
+#[derive(Debug, Clone)]
+pub struct FrameBuffer {
+	color : Texture,
+	depth : Vec<f32>,
+}
+
+impl FrameBuffer {
+	pub fn new(width : u32, height : u32) -> Self {
+		Self { color : Texture::allocate(width, height), depth : vec![1.0; (width * height) as usize] }
+	}
+
+	#[inline]
+	pub fn width(&self)  -> u32 { self.color.width  }
+	#[inline]
+	pub fn height(&self) -> u32 { self.color.height }
+
+	#[inline]
+	pub fn color(&self) -> &Texture { &self.color }
+
+	#[inline]
+	pub fn depth(&self, x : u32, y : u32) -> f32 {
+		self.depth[(y * self.width() + x) as usize]
+	}
+
+	// sets every pixel to "color" and the depth to the far plane
+	pub fn clear(&mut self, color : V4) {
+		self.color.texels.fill(color);
+		self.depth.fill(1.0);
+	}
+
+	pub fn to_image(&self) -> ImageBuffer<Rgb<u8>, Vec<u8>> { self.color.to_image() }
+}
+
+
+
+// -- PROGRAMS
+
+type VertexShader<'a, V, O>  = Box<dyn Fn(&V) -> (V4, O) + 'a>;
+type FragmentShader<'a, O>   = Box<dyn Fn(&O) -> V4 + 'a>;
+
+pub struct Program<'a, V, O> {
+	vertex     : VertexShader<'a, V, O>,
+	fragment   : FragmentShader<'a, O>,
+	indices    : Option<Vec<u32>>,
+	depth_test : bool,
+}
+
+// a vertex once processed: window coordinates & depth, 1 / w for perspective correction
+#[derive(Clone, Copy)]
+struct Projected<O> {
+	x       : f32,
+	y       : f32,
+	depth   : f32,
+	inv_w   : f32,
+	varying : O,
+}
+
+impl<'a, V, O : Varying> Program<'a, V, O> {
+	// "vertex" returns the clip coordinates of the vertex and the varyings to interpolate
+	pub fn new<VS, FS>(vertex : VS, fragment : FS) -> Self
+	where
+		VS : Fn(&V) -> (V4, O) + 'a,
+		FS : Fn(&O) -> V4 + 'a,
+	{
+		Self { vertex : Box::new(vertex), fragment : Box::new(fragment), indices : None, depth_test : true }
+	}
+
+	pub fn set_indices(&mut self, indices : &[u32]) {
+		self.indices = Some(indices.to_vec());
+	}
+
+	pub fn enable_depth_test(&mut self)  { self.depth_test = true;  }
+	pub fn disable_depth_test(&mut self) { self.depth_test = false; }
+
+	pub fn draw_buffer(&self, target : &mut FrameBuffer, vertices : &Buffer<V>, mode : DrawMode) -> Result<(), GLError> {
+		let n_elems = self.indices.as_ref().map_or(vertices.len(), |indices| indices.len());
+		self.draw_buffer_partial(target, vertices, 0, n_elems, mode)
+	}
+
+	// draws "how_many" elements starting from "from", in the indices if set, as "program::Program"
+	pub fn draw_buffer_partial(
+		&self,
+		target   : &mut FrameBuffer,
+		vertices : &Buffer<V>,
+		from     : usize,
+		how_many : usize,
+		mode     : DrawMode,
+		) -> Result<(), GLError>
+	{
+		let end = from.checked_add(how_many).ok_or(GLError::DrawRangeOutOfBounds)?;
+		let elements : Vec<usize> = match &self.indices {
+			Some(indices) => indices.get(from .. end).ok_or(GLError::DrawRangeOutOfBounds)?.iter().map(|&i| i as usize).collect(),
+			None          => (from .. end).collect(),
+		};
+		if elements.iter().any(|&i| i >= vertices.len()) {
+			return Err(GLError::DrawRangeOutOfBounds);
+		}
+
+		let projected : Vec<Option<Projected<O>>> = elements
+			.iter()
+			.map(|&i| self.project(&vertices.as_slice()[i], target))
+			.collect();
+
+		match mode {
+			DrawMode::Tris      => projected.chunks_exact(3).for_each(|tri| self.triangle(target, tri)),
+			DrawMode::TriStrip  => projected.windows(3).for_each(|tri| self.triangle(target, tri)),
+			DrawMode::Lines     => projected.chunks_exact(2).for_each(|line| self.line(target, line)),
+			DrawMode::LineStrip => projected.windows(2).for_each(|line| self.line(target, line)),
+			DrawMode::Points    => projected.iter().flatten().for_each(|point| {
+				self.fragment(target, point.x.floor(), point.y.floor(), point.depth, point.varying)
+			}),
+		}
+		Ok(())
+	}
+
+
+	fn project(&self, vertex : &V, target : &FrameBuffer) -> Option<Projected<O>> {
+		let (clip, varying) = (self.vertex)(vertex);
+		let [x, y, z, w] = clip.0;
+		if w <= f32::EPSILON {
+			return None;
+		}
+		let inv_w = 1.0 / w;
+		Some(Projected {
+			x     : (x * inv_w + 1.0) * 0.5 * target.width()  as f32,
+			y     : (y * inv_w + 1.0) * 0.5 * target.height() as f32,
+			depth : (z * inv_w + 1.0) * 0.5,
+			inv_w,
+			varying,
+		})
+	}
+
+	fn triangle(&self, target : &mut FrameBuffer, vertices : &[Option<Projected<O>>]) {
+		let (a, b, c) = match vertices {
+			[Some(a), Some(b), Some(c)] => (a, b, c),
+			_ => return,
+		};
+		// twice the signed area, positive for counter-clockwise triangles
+		let edge = |p : &Projected<O>, q : &Projected<O>, x : f32, y : f32| (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x);
+		let area = edge(a, b, c.x, c.y);
+		if area == 0.0 {
+			return;
+		}
+
+		let x_min = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+		let y_min = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+		let x_max = (a.x.max(b.x).max(c.x).ceil() as u32).min(target.width());
+		let y_max = (a.y.max(b.y).max(c.y).ceil() as u32).min(target.height());
+
+		for y in y_min .. y_max {
+			for x in x_min .. x_max {
+				let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+				// dividing by the signed area makes the weights positive inside, whatever the winding
+				let weights = [edge(b, c, px, py) / area, edge(c, a, px, py) / area, edge(a, b, px, py) / area];
+				if weights.iter().any(|&weight| weight < 0.0) {
+					continue;
+				}
+				let depth = weights[0] * a.depth + weights[1] * b.depth + weights[2] * c.depth;
+				let perspective = [weights[0] * a.inv_w, weights[1] * b.inv_w, weights[2] * c.inv_w];
+				let sum : f32 = perspective.iter().sum();
+				let varying = combine([a.varying, b.varying, c.varying], perspective.map(|weight| weight / sum));
+				self.fragment(target, x as f32, y as f32, depth, varying);
+			}
+		}
+	}
+
+	fn line(&self, target : &mut FrameBuffer, vertices : &[Option<Projected<O>>]) {
+		let (a, b) = match vertices {
+			[Some(a), Some(b)] => (a, b),
+			_ => return,
+		};
+		let n_steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as usize;
+		for step in 0 ..= n_steps {
+			let t = step as f32 / n_steps as f32;
+			let perspective = [(1.0 - t) * a.inv_w, t * b.inv_w];
+			let sum = perspective[0] + perspective[1];
+			let varying = combine([a.varying, b.varying], perspective.map(|weight| weight / sum));
+			let x = a.x + t * (b.x - a.x);
+			let y = a.y + t * (b.y - a.y);
+			let depth = a.depth + t * (b.depth - a.depth);
+			self.fragment(target, x.floor(), y.floor(), depth, varying);
+		}
+	}
+
+	// depth test & write of one pixel, "x" & "y" being whole window coordinates
+	fn fragment(&self, target : &mut FrameBuffer, x : f32, y : f32, depth : f32, varying : O) {
+		if x < 0.0 || y < 0.0 || x >= target.width() as f32 || y >= target.height() as f32 {
+			return;
+		}
+		if !(0.0 ..= 1.0).contains(&depth) {
+			return;
+		}
+		let index = (y as u32 * target.width() + x as u32) as usize;
+		if self.depth_test {
+			if depth >= target.depth[index] {
+				return;
+			}
+			target.depth[index] = depth;
+		}
+		target.color.texels[index] = (self.fragment)(&varying);
+	}
+}
+
+impl<V, O> std::fmt::Debug for Program<'_, V, O> {
+	fn fmt(&self, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Program")
+			.field("indices", &self.indices)
+			.field("depth_test", &self.depth_test)
+			.finish_non_exhaustive()
+	}
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RED   : V4 = Point([1.0, 0.0, 0.0, 1.0]);
+	const GREEN : V4 = Point([0.0, 1.0, 0.0, 1.0]);
+
+	fn flat<'a>(color : V4) -> Program<'a, V3, ()> {
+		Program::new(|position : &V3| (V4::new([position.0[0], position.0[1], position.0[2], 1.0]), ()), move |_| color)
+	}
+
+	#[test]
+	fn triangle_covers_pixel_centers_inside() {
+		let mut target = FrameBuffer::new(4, 4);
+		let vertices = Buffer::from_data(&[V3::new([-1.0, -1.0, 0.0]), V3::new([1.0, -1.0, 0.0]), V3::new([-1.0, 1.0, 0.0])]);
+		flat(RED).draw_buffer(&mut target, &vertices, DrawMode::Tris).unwrap();
+
+		// lower left half, row 0 at the bottom
+		assert_eq!(target.color().texel(0, 0), RED);
+		assert_eq!(target.color().texel(2, 0), RED);
+		assert_eq!(target.color().texel(3, 3), V4::new([0.0; 4]));
+		assert_eq!(target.depth(0, 0), 0.5);
+	}
+
+	#[test]
+	fn nearer_primitives_win_the_depth_test() {
+		let mut target = FrameBuffer::new(2, 2);
+		let quad = |z : f32| Buffer::from_data(&[
+			V3::new([-1.0, -1.0, z]), V3::new([1.0, -1.0, z]), V3::new([-1.0, 1.0, z]), V3::new([1.0, 1.0, z]),
+		]);
+		flat(RED).draw_buffer(&mut target, &quad(-0.5), DrawMode::TriStrip).unwrap();
+		flat(GREEN).draw_buffer(&mut target, &quad(0.5), DrawMode::TriStrip).unwrap();
+		assert_eq!(target.color().texel(1, 1), RED);
+
+		let mut no_depth = flat(GREEN);
+		no_depth.disable_depth_test();
+		no_depth.draw_buffer(&mut target, &quad(0.5), DrawMode::TriStrip).unwrap();
+		assert_eq!(target.color().texel(1, 1), GREEN);
+	}
+
+	#[test]
+	fn lines_points_and_indices() {
+		let mut target = FrameBuffer::new(4, 4);
+		let vertices = Buffer::from_data(&[V3::new([-0.875, -0.875, 0.0]), V3::new([0.875, -0.875, 0.0]), V3::new([0.125, 0.625, 0.0])]);
+		let mut program = flat(RED);
+		program.set_indices(&[0, 1]);
+		program.draw_buffer(&mut target, &vertices, DrawMode::Lines).unwrap();
+		assert!((0 .. 4).all(|x| target.color().texel(x, 0) == RED));
+
+		program.set_indices(&[2]);
+		program.draw_buffer(&mut target, &vertices, DrawMode::Points).unwrap();
+		assert_eq!(target.color().texel(2, 3), RED);
+
+		program.set_indices(&[0, 3]);
+		assert!(matches!(program.draw_buffer(&mut target, &vertices, DrawMode::Lines), Err(GLError::DrawRangeOutOfBounds)));
+	}
+
+	#[test]
+	fn sampling_is_bilinear_and_repeated() {
+		let mut texture = Texture::allocate(2, 1);
+		texture.set_texel(0, 0, RED);
+		texture.set_texel(1, 0, GREEN);
+		assert_eq!(texture.sample(V2::new([0.25, 0.5])), RED);
+		assert_eq!(texture.sample(V2::new([0.5, 0.5])), V4::new([0.5, 0.5, 0.0, 1.0]));
+		assert_eq!(texture.sample(V2::new([1.25, 0.5])), RED);
+		assert_eq!(Texture::allocate(0, 0).sample(V2::new([0.5, 0.5])), V4::new([0.0; 4]));
+	}
+
+	#[test]
+	fn camera_pipeline_projects_onto_the_center() {
+		let view = M44::look_at(&V3::new([0.0, 0.0, 5.0]), &V3::new([0.0; 3]), &V3::new([0.0, 1.0, 0.0]));
+		let projection = M44::perspective_projection(0.1, 100.0, 90.0, 1.0);
+		let transform = projection.dot(&view);
+
+		let mut target = FrameBuffer::new(16, 16);
+		let vertices = Buffer::from_data(&[V3::new([-1.0, -1.0, 0.0]), V3::new([1.0, -1.0, 0.0]), V3::new([0.0, 1.0, 0.0])]);
+		let program = Program::new(
+			|position : &V3| (transform.apply(&position.homo()), *position),
+			|position : &V3| V4::new([position.0[0] * 0.5 + 0.5, position.0[1] * 0.5 + 0.5, 0.0, 1.0]),
+		);
+		program.draw_buffer(&mut target, &vertices, DrawMode::Tris).unwrap();
+
+		assert_ne!(target.color().texel(8, 7), V4::new([0.0; 4]));
+		assert_eq!(target.color().texel(0, 0),  V4::new([0.0; 4]));
+		assert!(target.depth(8, 7) < 1.0);
+	}
+}
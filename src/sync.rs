@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use gl::types::*;
+
+use crate::error::*;
+
+
+
+// -- FENCES
+// a fence is signaled once the GPU has executed every command issued before its creation
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FenceStatus {
+	AlreadySignaled,
+	ConditionSatisfied,
+	TimeoutExpired,
+}
+
+impl FenceStatus {
+	pub fn is_signaled(&self) -> bool {
+		!matches!(self, Self::TimeoutExpired)
+	}
+}
+
+
+#[derive(Debug)]
+pub struct Fence {
+	sync : GLsync,
+}
+
+impl Fence {
+	#[track_caller]
+	pub fn new() -> Result<Self, GLError> {
+		let sync = unsafe {gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0)};
+		if sync.is_null() {
+			return Err(GLError::CouldNotCreateFence);
+		}
+		let fence = Self { sync };
+		check("Fence::new")?;
+		Ok(fence)
+	}
+
+	// blocks the CPU until the fence is signaled or "timeout" has elapsed
+	// pending commands are flushed, so that the fence is guaranteed to be signaled eventually
+	#[track_caller]
+	pub fn wait(&self, timeout : Duration) -> Result<FenceStatus, GLError> {
+		let timeout = timeout.as_nanos().min(gl::TIMEOUT_IGNORED as u128 - 1) as GLuint64;
+		let status = unsafe {gl::ClientWaitSync(self.sync, gl::SYNC_FLUSH_COMMANDS_BIT, timeout)};
+		check("Fence::wait")?;
+		match status {
+			gl::ALREADY_SIGNALED    => Ok(FenceStatus::AlreadySignaled),
+			gl::CONDITION_SATISFIED => Ok(FenceStatus::ConditionSatisfied),
+			gl::TIMEOUT_EXPIRED     => Ok(FenceStatus::TimeoutExpired),
+			_                       => Err(GLError::FenceWaitFailed),
+		}
+	}
+
+	// same as "wait" but errors out if the fence isn't signaled in time
+	#[track_caller]
+	pub fn wait_signaled(&self, timeout : Duration) -> Result<(), GLError> {
+		if self.wait(timeout)?.is_signaled() {
+			Ok(())
+		}
+		else {
+			Err(GLError::FenceTimeout)
+		}
+	}
+
+	// non-blocking poll
+	#[track_caller]
+	pub fn is_signaled(&self) -> Result<bool, GLError> {
+		Ok(self.wait(Duration::ZERO)?.is_signaled())
+	}
+
+	// makes the GPU (not the CPU) wait for the fence before executing further commands
+	// only useful across shared contexts
+	pub fn gpu_wait(&self) {
+		unsafe {gl::WaitSync(self.sync, 0, gl::TIMEOUT_IGNORED);}
+	}
+}
+
+impl Drop for Fence {
+	fn drop(&mut self) {
+		unsafe {gl::DeleteSync(self.sync);}
+	}
+}
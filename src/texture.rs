@@ -1,12 +1,18 @@
 use std::ffi::c_void;
 use std::ptr::null;
+use std::ptr::null_mut;
 
 use gl::types::GLuint;
 use image::DynamicImage;
 use image::GenericImageView;
 use image::ImageBuffer;
 use crate::GLError;
+use crate::error::check;
 use crate::attributes::GLType;
+use crate::buffer::{BufferBld, RawBuffer};
+use crate::sync::Fence;
+use crate::debug;
+use crate::dsa;
 
 #[derive(Debug, Clone, Copy)]
 pub enum TexAxis {
@@ -35,6 +41,27 @@ impl TexFormat {
 			TexFormat::DepthStencil => gl::DEPTH_STENCIL as gl::types::GLint,
 		}
 	}
+
+	// for immutable storage, see "dsa::texture_image_2d", and renderbuffers
+	pub(crate) fn to_sized_opengl_sym(self) -> gl::types::GLenum {
+		match self {
+			TexFormat::Monochrome   => gl::R8,
+			TexFormat::Rgb          => gl::RGB8,
+			TexFormat::Rgba         => gl::RGBA8,
+			TexFormat::Depth        => gl::DEPTH_COMPONENT24,
+			TexFormat::DepthStencil => gl::DEPTH24_STENCIL8,
+		}
+	}
+
+	fn n_components(self) -> usize {
+		match self {
+			TexFormat::Monochrome   => 1,
+			TexFormat::Rgb          => 3,
+			TexFormat::Rgba         => 4,
+			TexFormat::Depth        => 1,
+			TexFormat::DepthStencil => 1,
+		}
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -42,8 +69,7 @@ pub struct TextureId(pub gl::types::GLuint);
 
 impl TextureId {
 	fn new() -> Result<TextureId, GLError> {
-		let mut id = 0;
-		unsafe {gl::GenTextures(1, &mut id);}
+		let id = dsa::create_texture();
 		if id == 0 {
 			return Err(GLError::CouldNotCreateTexture);
 		}
@@ -61,6 +87,7 @@ pub struct Texture {
 
 impl Texture {
 
+	#[track_caller]
 	pub fn allocate(
 		width : u32, height : u32, 
 		format : TexFormat,
@@ -68,30 +95,22 @@ impl Texture {
 	{
 		let id = TextureId::new()?;
 
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, id.0);}
 		unsafe {
-			let opengl_sym = format.to_opengl_sym();
-			gl::TexImage2D(
-				gl::TEXTURE_2D, 
-				0, 
+			let opengl_sym = format.to_opengl_sym() as gl::types::GLuint;
+			dsa::texture_image_2d(
+				id.0, 
 				opengl_sym, 
-				width  as gl::types::GLint, 
-				height as gl::types::GLint, 
-				0, 
-				opengl_sym as gl::types::GLuint, // should be irrelevant ?
+				format.to_sized_opengl_sym(), 
+				width, 
+				height, 
+				opengl_sym, // should be irrelevant ?
 				GLType::Ubyte.to_opengl_sym(), // should be irrelevant ?
 				null(),
 			);
 		}
 
-		// Set interpolation
-		unsafe {
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as gl::types::GLint);
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR  as gl::types::GLint);
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as gl::types::GLint);	
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as gl::types::GLint);
-		}
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, 0);}
+		set_default_parameters(&id);
+		check("Texture::allocate")?;
 
 		Ok(Self {
 			id,
@@ -102,6 +121,7 @@ impl Texture {
 
 
 
+	#[track_caller]
 	pub fn new_stored_as(image: &DynamicImage, format : TexFormat) -> Result<Self, GLError> {
 		let id = TextureId::new()?;
 
@@ -137,29 +157,21 @@ impl Texture {
 			_ => Err(GLError::ImageTypeNotImplemented),
 		}?;
 
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, id.0);}
 		unsafe {
-			gl::TexImage2D(
-				gl::TEXTURE_2D, 
-				0, 
-				storage_type, 
-				width  as gl::types::GLint, 
-				height as gl::types::GLint, 
-				0, 
+			dsa::texture_image_2d(
+				id.0, 
+				storage_type as gl::types::GLuint, 
+				format.to_sized_opengl_sym(), 
+				width, 
+				height, 
 				image_format.to_opengl_sym() as gl::types::GLuint, 
 				pixel_type, 
 				image.as_bytes().as_ptr().cast(),
 			);
 		}
 
-		// Set interpolation
-		unsafe {
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as gl::types::GLint);
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR  as gl::types::GLint);
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as gl::types::GLint);	
-			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as gl::types::GLint);
-		}
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, 0);}
+		set_default_parameters(&id);
+		check("Texture::new_stored_as")?;
 
 		Ok(Self {
 			id,
@@ -168,6 +180,7 @@ impl Texture {
 		})
 	}
 
+	#[track_caller]
 	pub fn new(image: &DynamicImage) -> Result<Self, GLError> {
 		let format = match image {
 			DynamicImage::ImageRgb8(_)    |
@@ -205,18 +218,19 @@ impl Texture {
 		self.set_wrap(dimensions, gl::REPEAT as gl::types::GLint)
 	}
 
+	// shown by debugging tools, see "debug"
+	pub fn set_label(&self, label : &str) {
+		debug::set_label(gl::TEXTURE, self.id.0, label);
+	}
+
 	fn set_wrap(&self, dimensions :  &[TexAxis], repeat_param : gl::types::GLint) {
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, self.id.0);}
 		for dimension in dimensions {
 			let wrap_axis = match dimension {
 				TexAxis::UAxis => gl::TEXTURE_WRAP_S,
 				TexAxis::VAxis => gl::TEXTURE_WRAP_T,
 			};
-			unsafe {
-				gl::TexParameteri(gl::TEXTURE_2D, wrap_axis, repeat_param);	
-			}
+			dsa::texture_parameter(self.id.0, wrap_axis, repeat_param);
 		}
-		unsafe {gl::BindTexture(gl::TEXTURE_2D, 0);}
 	}
 
 	// account for more types and channel
@@ -224,17 +238,124 @@ impl Texture {
 		let capacity : usize = (self.width * self.height).try_into().unwrap();
 		let mut bytes : Vec<u8> = Vec::with_capacity(capacity * 3);
 		unsafe {
-			gl::BindTexture(gl::TEXTURE_2D, self.id.0);
-			gl::GetTexImage(
-				gl::TEXTURE_2D,
-				0,
+			dsa::get_texture_image(
+				self.id.0,
 				self.tex_format.to_opengl_sym() as gl::types::GLuint,
 				gl_type.to_opengl_sym(),
+				capacity * 3,
 				bytes.as_mut_ptr().cast(),
 			);
 			bytes.set_len(capacity * 3);
 		}
 		ImageBuffer::<image::Rgb<u8>, Vec<u8>>::from_raw(self.width, self.height, bytes)
 	}
+
+	// starts copying the texture into a pixel pack buffer without stalling the pipeline
+	// the pixels can be retrieved once the returned readback is ready
+	#[track_caller]
+	pub fn read_async(&self, gl_type : GLType) -> Result<TextureReadback, GLError> {
+		const PACK_ALIGNMENT : usize = 4;
+		let row_size = self.width as usize * self.tex_format.n_components() * gl_type.size();
+		let row_size = row_size.div_ceil(PACK_ALIGNMENT) * PACK_ALIGNMENT;
+		let n_bytes  = row_size * self.height as usize;
+
+		let buffer = BufferBld::pixel_pack().stream().for_read().allocate_raw(n_bytes)?;
+		unsafe {
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer.id.0);
+			dsa::get_texture_image(
+				self.id.0,
+				self.tex_format.to_opengl_sym() as gl::types::GLuint,
+				gl_type.to_opengl_sym(),
+				n_bytes,
+				null_mut(),
+			);
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+		}
+		// owned by the readback once it exists
+		let fence = check("Texture::read_async")
+			.and_then(|()| Fence::new())
+			.inspect_err(|_| unsafe {gl::DeleteBuffers(1, &buffer.id.0)})?;
+
+		Ok(TextureReadback { buffer, fence, n_bytes })
+	}
+}
+
+
+// nearest for minification, linear for magnification, repeated on both axes
+fn set_default_parameters(id : &TextureId) {
+	dsa::texture_parameter(id.0, gl::TEXTURE_MIN_FILTER, gl::NEAREST as gl::types::GLint);
+	dsa::texture_parameter(id.0, gl::TEXTURE_MAG_FILTER, gl::LINEAR  as gl::types::GLint);
+	dsa::texture_parameter(id.0, gl::TEXTURE_WRAP_S, gl::REPEAT as gl::types::GLint);
+	dsa::texture_parameter(id.0, gl::TEXTURE_WRAP_T, gl::REPEAT as gl::types::GLint);
+}
+
+
+#[derive(Debug)]
+pub struct TextureReadback {
+	buffer  : RawBuffer,
+	fence   : Fence,
+	n_bytes : usize,
+}
+
+impl TextureReadback {
+	#[track_caller]
+	pub fn is_ready(&self) -> Result<bool, GLError> {
+		self.fence.is_signaled()
+	}
+
+	// rows are padded to a multiple of 4 bytes
+	#[track_caller]
+	pub fn wait(&self, timeout : std::time::Duration) -> Result<Vec<u8>, GLError> {
+		self.fence.wait_signaled(timeout)?;
+		let mut bytes : Vec<u8> = vec![0; self.n_bytes];
+		unsafe {
+			dsa::get_buffer_sub_data(self.buffer.id.0, 0, self.n_bytes, bytes.as_mut_ptr().cast());
+		}
+		check("TextureReadback::wait")?;
+		Ok(bytes)
+	}
+}
+
+impl Drop for TextureReadback {
+	fn drop(&mut self) {
+		unsafe {gl::DeleteBuffers(1, &self.buffer.id.0);}
+	}
 }
 
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::GLWrap;
+	use crate::trace::*;
+
+	// fences can't be created
+	struct NoFence(Recorder);
+
+	impl Backend for NoFence {
+		fn call(&mut self, call : &Call) -> Value {
+			let value = self.0.call(call);
+			if call.name == "FenceSync" { Value::Int(0) } else { value }
+		}
+	}
+
+	#[test]
+	fn failed_readbacks_free_their_buffer() {
+		let recorder = Recorder::new();
+		let trace    = recorder.trace();
+		let _gl      = GLWrap::with_backend(NoFence(recorder));
+
+		let texture = Texture::allocate(4, 4, TexFormat::Rgba).unwrap();
+		trace.clear();
+		let readback = texture.read_async(GLType::Ubyte);
+		assert!(matches!(readback, Err(GLError::CouldNotCreateFence)));
+
+		let created = trace.named("GenBuffers").len() + trace.named("CreateBuffers").len();
+		assert_eq!(created, 1);
+		assert_eq!(trace.named("DeleteBuffers").len(), 1);
+	}
+}
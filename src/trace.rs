@@ -0,0 +1,575 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::rc::Rc;
+use std::sync::Once;
+
+use gl::types::*;
+
+
+
+// -- BACKENDS
+// GL entry points can be loaded with stubs that hand every call to a "Backend" instead of a driver
+// "Recorder" logs the calls with their arguments, so that tests can check what a wrapper issued
+// without any GPU, e.g.
+//
+//   let recorder = Recorder::new();
+//   let trace    = recorder.trace();
+//   let _gl      = GLWrap::with_backend(recorder);
+//   program.draw_buffer(DrawMode::Tris)?;
+//   assert_eq!(trace.named("DrawElements")[0].args[2], Value::of(gl::UNSIGNED_INT));
+//
+// entry points are global, but backends are per thread: tests can run in parallel
+// calls from a thread without a backend do nothing and return 0
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+	Int(i64),
+	Float(f64),
+	// pointers & callbacks, as addresses
+	Ptr(usize),
+}
+
+impl Value {
+	pub fn of<T : Arg>(arg : T) -> Self { arg.to_value() }
+
+	pub fn as_int(self) -> i64 {
+		match self {
+			Value::Int(value)   => value,
+			Value::Float(value) => value as i64,
+			Value::Ptr(value)   => value as i64,
+		}
+	}
+}
+
+// argument types of the GL entry points
+pub trait Arg {
+	fn to_value(self) -> Value;
+}
+
+macro_rules! int_arg {
+	($($ty:ty),*) => {$(
+		impl Arg for $ty {
+			fn to_value(self) -> Value { Value::Int(self as i64) }
+		}
+	)*};
+}
+int_arg!(u8, i32, u32, i64, u64, isize);
+
+impl Arg for f32 {
+	fn to_value(self) -> Value { Value::Float(self as f64) }
+}
+
+impl Arg for f64 {
+	fn to_value(self) -> Value { Value::Float(self) }
+}
+
+impl<T> Arg for *const T {
+	fn to_value(self) -> Value { Value::Ptr(self as usize) }
+}
+
+impl<T> Arg for *mut T {
+	fn to_value(self) -> Value { Value::Ptr(self as usize) }
+}
+
+impl Arg for GLDEBUGPROC {
+	fn to_value(self) -> Value { Value::Ptr(self.map_or(0, |callback| callback as usize)) }
+}
+
+// return & output types of the GL entry points
+trait FromValue {
+	fn from_value(value : Value) -> Self;
+}
+
+macro_rules! int_from_value {
+	($($ty:ty),*) => {$(
+		impl FromValue for $ty {
+			fn from_value(value : Value) -> Self { value.as_int() as $ty }
+		}
+	)*};
+}
+int_from_value!(u8, i32, u32, i64, u64);
+
+impl FromValue for f32 {
+	fn from_value(value : Value) -> Self {
+		match value {
+			Value::Float(value) => value as f32,
+			value               => value.as_int() as f32,
+		}
+	}
+}
+
+impl<T> FromValue for *const T {
+	fn from_value(value : Value) -> Self { value.as_int() as usize as *const T }
+}
+
+impl<T> FromValue for *mut T {
+	fn from_value(value : Value) -> Self { value.as_int() as usize as *mut T }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+	// without the "gl" prefix, e.g. "DrawElements"
+	pub name : &'static str,
+	pub args : Vec<Value>,
+}
+
+pub trait Backend {
+	// the returned value is the result of functions returning one, and is written through the
+	// output pointer of queries (glGet*v) ; object creation (glGen*, glCreate*) gets consecutive
+	// names from it
+	// must not call GL itself
+	fn call(&mut self, call : &Call) -> Value;
+}
+
+
+thread_local! {
+	static BACKEND : RefCell<Option<Box<dyn Backend>>> = RefCell::new(None);
+}
+
+pub(crate) fn install(backend : Box<dyn Backend>) {
+	BACKEND.with(|current| *current.borrow_mut() = Some(backend));
+}
+
+// replaces the entry points for the whole process, once
+pub(crate) fn load_stubs() {
+	static LOAD : Once = Once::new();
+	LOAD.call_once(|| gl::load_with(stub_address));
+}
+
+fn dispatch(name : &'static str, args : Vec<Value>) -> Value {
+	let call = Call { name, args };
+	BACKEND.with(|backend| match backend.try_borrow_mut() {
+		Ok(mut backend) => backend.as_mut().map_or(Value::Int(0), |backend| backend.call(&call)),
+		Err(_)          => Value::Int(0),
+	})
+}
+
+
+
+// -- RECORDER
+
+// every call, in order, shared with the "Recorder" that fills it
+#[derive(Debug, Clone, Default)]
+pub struct Trace(Rc<RefCell<Vec<Call>>>);
+
+impl Trace {
+	pub fn calls(&self) -> Vec<Call> { self.0.borrow().clone() }
+
+	pub fn named(&self, name : &str) -> Vec<Call> {
+		self.0.borrow().iter().filter(|call| call.name == name).cloned().collect()
+	}
+
+	pub fn clear(&self) { self.0.borrow_mut().clear() }
+}
+
+// answers like a GL 3.3 driver where everything succeeds: objects get fresh names, shaders compile,
+// programs link, framebuffers are complete, buffers have the size they were given ; integers
+// returned by glGetIntegerv and the active uniforms of every program can be set
+#[derive(Debug)]
+pub struct Recorder {
+	trace     : Trace,
+	next_name : i64,
+	integers  : HashMap<GLenum, i64>,
+	// buffer bound to each target, size of each buffer
+	bound_buffers : HashMap<i64, i64>,
+	buffer_sizes  : HashMap<i64, i64>,
+	// CPU memory handed out by glMapBufferRange, per buffer
+	mapped        : HashMap<i64, Vec<u8>>,
+	// reported by glGetActiveUniform, located at their index
+	uniforms      : Vec<ActiveUniform>,
+}
+
+#[derive(Debug)]
+struct ActiveUniform {
+	name    : CString,
+	gl_type : GLenum,
+}
+
+impl Recorder {
+	pub fn new() -> Self {
+		let integers = [
+			(gl::MAJOR_VERSION,                    3),
+			(gl::MINOR_VERSION,                    3),
+			(gl::MAX_TEXTURE_SIZE,                 16384),
+			(gl::MAX_VERTEX_ATTRIBS,               16),
+			(gl::MAX_UNIFORM_BLOCK_SIZE,           65536),
+			(gl::MAX_DRAW_BUFFERS,                 8),
+			(gl::MAX_SAMPLES,                      8),
+			(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, 32),
+		].into_iter().collect();
+		Self {
+			trace         : Trace::default(),
+			next_name     : 1,
+			integers,
+			bound_buffers : HashMap::new(),
+			buffer_sizes  : HashMap::new(),
+			mapped        : HashMap::new(),
+			uniforms      : Vec::new(),
+		}
+	}
+
+	pub fn trace(&self) -> Trace { self.trace.clone() }
+
+	pub fn with_integer(mut self, name : GLenum, value : i64) -> Self {
+		self.integers.insert(name, value);
+		self
+	}
+
+	// a uniform of every program, not an array
+	pub fn with_uniform(mut self, name : &str, gl_type : GLenum) -> Self {
+		self.uniforms.push(ActiveUniform { name : CString::new(name).unwrap(), gl_type });
+		self
+	}
+
+	// zeroed, whatever the buffer holds
+	fn map(&mut self, buffer : i64, offset : i64) -> Value {
+		let size = self.buffer_sizes.get(&buffer).copied().unwrap_or(0);
+		let memory = self.mapped.entry(buffer).or_default();
+		memory.clear();
+		memory.resize(size.max(0) as usize, 0);
+		Value::Ptr(memory.as_mut_ptr() as usize + offset as usize)
+	}
+
+	fn new_names(&mut self, n : i64) -> Value {
+		let first = self.next_name;
+		self.next_name += n.max(1);
+		Value::Int(first)
+	}
+
+	fn respond(&mut self, call : &Call) -> Value {
+		let arg = |i : usize| call.args.get(i).map_or(0, |value| value.as_int());
+		match call.name {
+			"CreateProgram" | "CreateShader" | "FenceSync"    => self.new_names(1),
+			"CreateTextures"                                  => self.new_names(arg(1)),
+			name if name.starts_with("Gen") || name.starts_with("Create") => self.new_names(arg(0)),
+
+			"CheckFramebufferStatus" | "CheckNamedFramebufferStatus" => Value::Int(gl::FRAMEBUFFER_COMPLETE as i64),
+			"ClientWaitSync"                                  => Value::Int(gl::ALREADY_SIGNALED as i64),
+			"UnmapBuffer" | "UnmapNamedBuffer"                => Value::Int(gl::TRUE as i64),
+
+			"BindBuffer" => {
+				self.bound_buffers.insert(arg(0), arg(1));
+				Value::Int(0)
+			},
+			"BufferData" | "BufferStorage" => {
+				let buffer = self.bound_buffers.get(&arg(0)).copied().unwrap_or(0);
+				self.buffer_sizes.insert(buffer, arg(1));
+				Value::Int(0)
+			},
+			"NamedBufferData" | "NamedBufferStorage" => {
+				self.buffer_sizes.insert(arg(0), arg(1));
+				Value::Int(0)
+			},
+			"GetBufferParameteri64v" => {
+				let buffer = self.bound_buffers.get(&arg(0)).copied().unwrap_or(0);
+				Value::Int(self.buffer_sizes.get(&buffer).copied().unwrap_or(0))
+			},
+			"GetNamedBufferParameteri64v" => Value::Int(self.buffer_sizes.get(&arg(0)).copied().unwrap_or(0)),
+			"MapBufferRange" => {
+				let buffer = self.bound_buffers.get(&arg(0)).copied().unwrap_or(0);
+				self.map(buffer, arg(1))
+			},
+			"MapNamedBufferRange" => self.map(arg(0), arg(1)),
+
+			// the uniform itself, read by the stub
+			"GetActiveUniform" => {
+				self.uniforms.get(arg(1) as usize).map_or(Value::Int(0), |uniform| Value::Ptr(uniform as *const ActiveUniform as usize))
+			},
+			// other names are located at 0, as if they existed
+			"GetUniformLocation" if arg(1) != 0 => {
+				// the name outlives the call
+				let name = unsafe {CStr::from_ptr(arg(1) as usize as *const GLchar)};
+				let index = self.uniforms.iter().position(|uniform| uniform.name.as_c_str() == name);
+				Value::Int(index.unwrap_or(0) as i64)
+			},
+
+			"GetIntegerv" | "GetInteger64v" => Value::Int(self.integers.get(&(arg(0) as GLenum)).copied().unwrap_or(0)),
+			"GetShaderiv" | "GetProgramiv" | "GetProgramPipelineiv" => {
+				match arg(1) as GLenum {
+					gl::COMPILE_STATUS | gl::LINK_STATUS | gl::VALIDATE_STATUS => Value::Int(gl::TRUE as i64),
+					gl::ACTIVE_UNIFORMS if call.name == "GetProgramiv" => Value::Int(self.uniforms.len() as i64),
+					_ => Value::Int(0),
+				}
+			},
+			_ => Value::Int(0),
+		}
+	}
+}
+
+impl Default for Recorder {
+	fn default() -> Self { Self::new() }
+}
+
+impl Backend for Recorder {
+	fn call(&mut self, call : &Call) -> Value {
+		let value = self.respond(call);
+		self.trace.0.borrow_mut().push(call.clone());
+		value
+	}
+}
+
+
+
+// -- STUBS
+// one per entry point used by the crate, with the signature of the "gl" crate
+
+macro_rules! write_out {
+	(names, $value:ident, $n:ident, $names:ident) => {
+		if !$names.is_null() {
+			let first = $value.as_int() as GLuint;
+			for i in 0 .. $n.max(0) as usize {
+				unsafe {*$names.add(i) = first + i as GLuint};
+			}
+		}
+	};
+	// "value" points to an "ActiveUniform" of the recorder, or is 0
+	(active_uniform, $value:ident, $buf_size:ident, $length:ident, $size:ident, $type_:ident, $name:ident) => {
+		let uniform = $value.as_int() as usize as *const ActiveUniform;
+		if !uniform.is_null() && !$name.is_null() && $buf_size > 0 {
+			let uniform = unsafe {&*uniform};
+			let bytes = uniform.name.as_bytes();
+			let length = bytes.len().min($buf_size as usize - 1);
+			unsafe {
+				std::ptr::copy_nonoverlapping(bytes.as_ptr() as *const GLchar, $name, length);
+				*$name.add(length) = 0;
+				if !$length.is_null() { *$length = length as GLsizei; }
+				if !$size.is_null()   { *$size = 1; }
+				if !$type_.is_null()  { *$type_ = uniform.gl_type; }
+			}
+		}
+	};
+	(out, $value:ident, $out:ident) => {
+		if !$out.is_null() {
+			unsafe {*$out = FromValue::from_value($value)};
+		}
+	};
+}
+
+macro_rules! stubs {
+	($( fn $name:ident ( $( $arg:ident : $ty:ty ),* ) $( -> $ret:ty )? $( => $kind:ident ( $( $out:ident ),* ) )? ; )*) => {
+		$(
+			#[allow(non_snake_case, unused_variables)]
+			extern "system" fn $name( $( $arg : $ty ),* ) $( -> $ret )? {
+				let value = dispatch(stringify!($name), vec![ $( Value::of($arg) ),* ]);
+				$( write_out!($kind, value, $( $out ),*); )?
+				$( <$ret as FromValue>::from_value(value) )?
+			}
+		)*
+
+		// the "gl" crate asks for e.g. "glDrawElements", then for fallbacks such as "glDrawElementsEXT"
+		fn stub_address(name : &str) -> *const c_void {
+			match name.strip_prefix("gl") {
+				$( Some(stringify!($name)) => $name as *const c_void, )*
+				_ => std::ptr::null(),
+			}
+		}
+	};
+}
+
+stubs! {
+	fn ActiveTexture(texture : GLenum);
+	fn AttachShader(program : GLuint, shader : GLuint);
+	fn BeginConditionalRender(id : GLuint, mode : GLenum);
+	fn BeginQuery(target : GLenum, id : GLuint);
+	fn BeginTransformFeedback(primitiveMode : GLenum);
+	fn BindBuffer(target : GLenum, buffer : GLuint);
+	fn BindBufferBase(target : GLenum, index : GLuint, buffer : GLuint);
+	fn BindFramebuffer(target : GLenum, framebuffer : GLuint);
+	fn BindProgramPipeline(pipeline : GLuint);
+	fn BindRenderbuffer(target : GLenum, renderbuffer : GLuint);
+	fn BindTexture(target : GLenum, texture : GLuint);
+	fn BindVertexArray(array : GLuint);
+	fn BufferData(target : GLenum, size : GLsizeiptr, data : *const c_void, usage : GLenum);
+	fn BufferStorage(target : GLenum, size : GLsizeiptr, data : *const c_void, flags : GLbitfield);
+	fn BufferSubData(target : GLenum, offset : GLintptr, size : GLsizeiptr, data : *const c_void);
+	fn CheckFramebufferStatus(target : GLenum) -> GLenum;
+	fn CheckNamedFramebufferStatus(framebuffer : GLuint, target : GLenum) -> GLenum;
+	fn Clear(mask : GLbitfield);
+	fn ClearColor(red : GLfloat, green : GLfloat, blue : GLfloat, alpha : GLfloat);
+	fn ClientWaitSync(sync : GLsync, flags : GLbitfield, timeout : GLuint64) -> GLenum;
+	fn CompileShader(shader : GLuint);
+	fn CopyBufferSubData(readTarget : GLenum, writeTarget : GLenum, readOffset : GLintptr, writeOffset : GLintptr, size : GLsizeiptr);
+	fn CopyNamedBufferSubData(readBuffer : GLuint, writeBuffer : GLuint, readOffset : GLintptr, writeOffset : GLintptr, size : GLsizeiptr);
+	fn CreateBuffers(n : GLsizei, buffers : *mut GLuint) => names(n, buffers);
+	fn CreateFramebuffers(n : GLsizei, framebuffers : *mut GLuint) => names(n, framebuffers);
+	fn CreateProgram() -> GLuint;
+	fn CreateRenderbuffers(n : GLsizei, renderbuffers : *mut GLuint) => names(n, renderbuffers);
+	fn CreateShader(type_ : GLenum) -> GLuint;
+	fn CreateTextures(target : GLenum, n : GLsizei, textures : *mut GLuint) => names(n, textures);
+	fn CreateVertexArrays(n : GLsizei, arrays : *mut GLuint) => names(n, arrays);
+	fn DebugMessageCallback(callback : GLDEBUGPROC, userParam : *const c_void);
+	fn DebugMessageControl(source : GLenum, type_ : GLenum, severity : GLenum, count : GLsizei, ids : *const GLuint, enabled : GLboolean);
+	fn DeleteBuffers(n : GLsizei, buffers : *const GLuint);
+	fn DeleteFramebuffers(n : GLsizei, framebuffers : *const GLuint);
+	fn DeleteProgram(program : GLuint);
+	fn DeleteProgramPipelines(n : GLsizei, pipelines : *const GLuint);
+	fn DeleteQueries(n : GLsizei, ids : *const GLuint);
+	fn DeleteRenderbuffers(n : GLsizei, renderbuffers : *const GLuint);
+	fn DeleteShader(shader : GLuint);
+	fn DeleteSync(sync : GLsync);
+	fn DeleteTextures(n : GLsizei, textures : *const GLuint);
+	fn DeleteVertexArrays(n : GLsizei, arrays : *const GLuint);
+	fn DepthFunc(func : GLenum);
+	fn DetachShader(program : GLuint, shader : GLuint);
+	fn Disable(cap : GLenum);
+	fn DrawArrays(mode : GLenum, first : GLint, count : GLsizei);
+	fn DrawArraysIndirect(mode : GLenum, indirect : *const c_void);
+	fn DrawElements(mode : GLenum, count : GLsizei, type_ : GLenum, indices : *const c_void);
+	fn DrawElementsBaseVertex(mode : GLenum, count : GLsizei, type_ : GLenum, indices : *const c_void, basevertex : GLint);
+	fn DrawElementsIndirect(mode : GLenum, type_ : GLenum, indirect : *const c_void);
+	fn Enable(cap : GLenum);
+	fn EnableVertexArrayAttrib(vaobj : GLuint, index : GLuint);
+	fn EnableVertexAttribArray(index : GLuint);
+	fn EndConditionalRender();
+	fn EndQuery(target : GLenum);
+	fn EndTransformFeedback();
+	fn FenceSync(condition : GLenum, flags : GLbitfield) -> GLsync;
+	fn FramebufferRenderbuffer(target : GLenum, attachment : GLenum, renderbuffertarget : GLenum, renderbuffer : GLuint);
+	fn FramebufferTexture2D(target : GLenum, attachment : GLenum, textarget : GLenum, texture : GLuint, level : GLint);
+	fn GenBuffers(n : GLsizei, buffers : *mut GLuint) => names(n, buffers);
+	fn GenFramebuffers(n : GLsizei, framebuffers : *mut GLuint) => names(n, framebuffers);
+	fn GenProgramPipelines(n : GLsizei, pipelines : *mut GLuint) => names(n, pipelines);
+	fn GenQueries(n : GLsizei, ids : *mut GLuint) => names(n, ids);
+	fn GenRenderbuffers(n : GLsizei, renderbuffers : *mut GLuint) => names(n, renderbuffers);
+	fn GenTextures(n : GLsizei, textures : *mut GLuint) => names(n, textures);
+	fn GenVertexArrays(n : GLsizei, arrays : *mut GLuint) => names(n, arrays);
+	fn GetActiveAttrib(program : GLuint, index : GLuint, bufSize : GLsizei, length : *mut GLsizei, size : *mut GLint, type_ : *mut GLenum, name : *mut GLchar) => out(length);
+	fn GetActiveUniform(program : GLuint, index : GLuint, bufSize : GLsizei, length : *mut GLsizei, size : *mut GLint, type_ : *mut GLenum, name : *mut GLchar) => active_uniform(bufSize, length, size, type_, name);
+	fn GetActiveUniformBlockName(program : GLuint, uniformBlockIndex : GLuint, bufSize : GLsizei, length : *mut GLsizei, uniformBlockName : *mut GLchar) => out(length);
+	fn GetActiveUniformBlockiv(program : GLuint, uniformBlockIndex : GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetActiveUniformsiv(program : GLuint, uniformCount : GLsizei, uniformIndices : *const GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetAttribLocation(program : GLuint, name : *const GLchar) -> GLint;
+	fn GetBufferParameteri64v(target : GLenum, pname : GLenum, params : *mut GLint64) => out(params);
+	fn GetBufferSubData(target : GLenum, offset : GLintptr, size : GLsizeiptr, data : *mut c_void);
+	fn GetError() -> GLenum;
+	fn GetInteger64v(pname : GLenum, data : *mut GLint64) => out(data);
+	fn GetIntegerv(pname : GLenum, data : *mut GLint) => out(data);
+	fn GetNamedBufferParameteri64v(buffer : GLuint, pname : GLenum, params : *mut GLint64) => out(params);
+	fn GetNamedBufferSubData(buffer : GLuint, offset : GLintptr, size : GLsizeiptr, data : *mut c_void);
+	fn GetProgramBinary(program : GLuint, bufSize : GLsizei, length : *mut GLsizei, binaryFormat : *mut GLenum, binary : *mut c_void) => out(length);
+	fn GetProgramInfoLog(program : GLuint, bufSize : GLsizei, length : *mut GLsizei, infoLog : *mut GLchar) => out(length);
+	fn GetProgramPipelineInfoLog(pipeline : GLuint, bufSize : GLsizei, length : *mut GLsizei, infoLog : *mut GLchar) => out(length);
+	fn GetProgramPipelineiv(pipeline : GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetProgramiv(program : GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetQueryObjectiv(id : GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetQueryObjectui64v(id : GLuint, pname : GLenum, params : *mut GLuint64) => out(params);
+	fn GetShaderInfoLog(shader : GLuint, bufSize : GLsizei, length : *mut GLsizei, infoLog : *mut GLchar) => out(length);
+	fn GetShaderiv(shader : GLuint, pname : GLenum, params : *mut GLint) => out(params);
+	fn GetString(name : GLenum) -> *const GLubyte;
+	fn GetStringi(name : GLenum, index : GLuint) -> *const GLubyte;
+	fn GetTexImage(target : GLenum, level : GLint, format : GLenum, type_ : GLenum, pixels : *mut c_void);
+	fn GetTextureImage(texture : GLuint, level : GLint, format : GLenum, type_ : GLenum, bufSize : GLsizei, pixels : *mut c_void);
+	fn GetUniformBlockIndex(program : GLuint, uniformBlockName : *const GLchar) -> GLuint;
+	fn GetUniformIndices(program : GLuint, uniformCount : GLsizei, uniformNames : *const *const GLchar, uniformIndices : *mut GLuint) => out(uniformIndices);
+	fn GetUniformLocation(program : GLuint, name : *const GLchar) -> GLint;
+	fn GetUniformfv(program : GLuint, location : GLint, params : *mut GLfloat) => out(params);
+	fn GetUniformiv(program : GLuint, location : GLint, params : *mut GLint) => out(params);
+	fn GetUniformuiv(program : GLuint, location : GLint, params : *mut GLuint) => out(params);
+	fn LineWidth(width : GLfloat);
+	fn LinkProgram(program : GLuint);
+	fn MapBufferRange(target : GLenum, offset : GLintptr, length : GLsizeiptr, access : GLbitfield) -> *mut c_void;
+	fn MapNamedBufferRange(buffer : GLuint, offset : GLintptr, length : GLsizeiptr, access : GLbitfield) -> *mut c_void;
+	fn MultiDrawArrays(mode : GLenum, first : *const GLint, count : *const GLsizei, drawcount : GLsizei);
+	fn MultiDrawArraysIndirect(mode : GLenum, indirect : *const c_void, drawcount : GLsizei, stride : GLsizei);
+	fn MultiDrawElements(mode : GLenum, count : *const GLsizei, type_ : GLenum, indices : *const *const c_void, drawcount : GLsizei);
+	fn MultiDrawElementsIndirect(mode : GLenum, type_ : GLenum, indirect : *const c_void, drawcount : GLsizei, stride : GLsizei);
+	fn NamedBufferData(buffer : GLuint, size : GLsizeiptr, data : *const c_void, usage : GLenum);
+	fn NamedBufferStorage(buffer : GLuint, size : GLsizeiptr, data : *const c_void, flags : GLbitfield);
+	fn NamedBufferSubData(buffer : GLuint, offset : GLintptr, size : GLsizeiptr, data : *const c_void);
+	fn NamedFramebufferRenderbuffer(framebuffer : GLuint, attachment : GLenum, renderbuffertarget : GLenum, renderbuffer : GLuint);
+	fn NamedFramebufferTexture(framebuffer : GLuint, attachment : GLenum, texture : GLuint, level : GLint);
+	fn NamedRenderbufferStorageMultisample(renderbuffer : GLuint, samples : GLsizei, internalformat : GLenum, width : GLsizei, height : GLsizei);
+	fn ObjectLabel(identifier : GLenum, name : GLuint, length : GLsizei, label : *const GLchar);
+	fn PointSize(size : GLfloat);
+	fn PopDebugGroup();
+	fn PrimitiveRestartIndex(index : GLuint);
+	fn ProgramBinary(program : GLuint, binaryFormat : GLenum, binary : *const c_void, length : GLsizei);
+	fn ProgramParameteri(program : GLuint, pname : GLenum, value : GLint);
+	fn PushDebugGroup(source : GLenum, id : GLuint, length : GLsizei, message : *const GLchar);
+	fn QueryCounter(id : GLuint, target : GLenum);
+	fn RenderbufferStorageMultisample(target : GLenum, samples : GLsizei, internalformat : GLenum, width : GLsizei, height : GLsizei);
+	fn ShaderSource(shader : GLuint, count : GLsizei, string : *const *const GLchar, length : *const GLint);
+	fn TexImage2D(target : GLenum, level : GLint, internalformat : GLint, width : GLsizei, height : GLsizei, border : GLint, format : GLenum, type_ : GLenum, pixels : *const c_void);
+	fn TexParameteri(target : GLenum, pname : GLenum, param : GLint);
+	fn TextureParameteri(texture : GLuint, pname : GLenum, param : GLint);
+	fn TextureStorage2D(texture : GLuint, levels : GLsizei, internalformat : GLenum, width : GLsizei, height : GLsizei);
+	fn TextureSubImage2D(texture : GLuint, level : GLint, xoffset : GLint, yoffset : GLint, width : GLsizei, height : GLsizei, format : GLenum, type_ : GLenum, pixels : *const c_void);
+	fn TransformFeedbackVaryings(program : GLuint, count : GLsizei, varyings : *const *const GLchar, bufferMode : GLenum);
+	fn Uniform1fv(location : GLint, count : GLsizei, value : *const GLfloat);
+	fn Uniform1iv(location : GLint, count : GLsizei, value : *const GLint);
+	fn Uniform1uiv(location : GLint, count : GLsizei, value : *const GLuint);
+	fn Uniform2fv(location : GLint, count : GLsizei, value : *const GLfloat);
+	fn Uniform2iv(location : GLint, count : GLsizei, value : *const GLint);
+	fn Uniform2uiv(location : GLint, count : GLsizei, value : *const GLuint);
+	fn Uniform3fv(location : GLint, count : GLsizei, value : *const GLfloat);
+	fn Uniform3iv(location : GLint, count : GLsizei, value : *const GLint);
+	fn Uniform3uiv(location : GLint, count : GLsizei, value : *const GLuint);
+	fn Uniform4fv(location : GLint, count : GLsizei, value : *const GLfloat);
+	fn Uniform4iv(location : GLint, count : GLsizei, value : *const GLint);
+	fn Uniform4uiv(location : GLint, count : GLsizei, value : *const GLuint);
+	fn UniformBlockBinding(program : GLuint, uniformBlockIndex : GLuint, uniformBlockBinding : GLuint);
+	fn UniformMatrix2fv(location : GLint, count : GLsizei, transpose : GLboolean, value : *const GLfloat);
+	fn UniformMatrix3fv(location : GLint, count : GLsizei, transpose : GLboolean, value : *const GLfloat);
+	fn UniformMatrix4fv(location : GLint, count : GLsizei, transpose : GLboolean, value : *const GLfloat);
+	fn UnmapBuffer(target : GLenum) -> GLboolean;
+	fn UnmapNamedBuffer(buffer : GLuint) -> GLboolean;
+	fn UseProgram(program : GLuint);
+	fn UseProgramStages(pipeline : GLuint, stages : GLbitfield, program : GLuint);
+	fn ValidateProgramPipeline(pipeline : GLuint);
+	fn VertexArrayAttribBinding(vaobj : GLuint, attribindex : GLuint, bindingindex : GLuint);
+	fn VertexArrayAttribFormat(vaobj : GLuint, attribindex : GLuint, size : GLint, type_ : GLenum, normalized : GLboolean, relativeoffset : GLuint);
+	fn VertexArrayAttribIFormat(vaobj : GLuint, attribindex : GLuint, size : GLint, type_ : GLenum, relativeoffset : GLuint);
+	fn VertexArrayVertexBuffer(vaobj : GLuint, bindingindex : GLuint, buffer : GLuint, offset : GLintptr, stride : GLsizei);
+	fn VertexAttribIPointer(index : GLuint, size : GLint, type_ : GLenum, stride : GLsizei, pointer : *const c_void);
+	fn VertexAttribPointer(index : GLuint, size : GLint, type_ : GLenum, normalized : GLboolean, stride : GLsizei, pointer : *const c_void);
+	fn Viewport(x : GLint, y : GLint, width : GLsizei, height : GLsizei);
+	fn WaitSync(sync : GLsync, flags : GLbitfield, timeout : GLuint64);
+}
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn recorder_logs_calls_and_answers_queries() {
+		let mut recorder = Recorder::new().with_integer(gl::MAX_SAMPLES, 4);
+		let trace = recorder.trace();
+
+		let first  = recorder.call(&Call { name : "GenBuffers", args : vec![Value::of(2), Value::Ptr(0)] });
+		let second = recorder.call(&Call { name : "CreateShader", args : vec![Value::of(gl::VERTEX_SHADER)] });
+		assert_eq!((first, second), (Value::Int(1), Value::Int(3)));
+
+		let samples = recorder.call(&Call { name : "GetIntegerv", args : vec![Value::of(gl::MAX_SAMPLES), Value::Ptr(0)] });
+		assert_eq!(samples, Value::Int(4));
+
+		assert_eq!(trace.calls().len(), 3);
+		assert_eq!(trace.named("CreateShader")[0].args, vec![Value::of(gl::VERTEX_SHADER)]);
+		trace.clear();
+		assert!(trace.calls().is_empty());
+	}
+
+	#[test]
+	fn stubs_write_names_and_outputs() {
+		install(Box::new(Recorder::new()));
+
+		let mut names = [0 as GLuint; 3];
+		GenTextures(3, names.as_mut_ptr());
+		assert_eq!(names, [1, 2, 3]);
+
+		let mut status = 0;
+		GetShaderiv(names[0], gl::COMPILE_STATUS, &mut status);
+		assert_eq!(status, gl::TRUE as GLint);
+
+		assert_eq!(CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
+		assert!(!stub_address("glDrawElements").is_null());
+		assert!(stub_address("glDrawElementsEXT").is_null());
+	}
+}
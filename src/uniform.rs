@@ -1,4 +1,5 @@
 use crate::math3d::*;
+use crate::error::*;
 use crate::program::*;
 
 use gl::types::*;
@@ -15,103 +16,261 @@ pub struct LayoutLocation(pub GLint);
 pub struct Uniform<'a, T : UniformData + ?Sized> {
 	pub program_id  : &'a ProgramId,
 	pub location    : LayoutLocation,
+	// GL type of the uniform (of its elements for an array), 0 if unknown or in release builds
+	pub gl_type     : GLenum,
 	pub phantom     : std::marker::PhantomData<T>
 }
 
 impl<'a, T : UniformData + ?Sized> Uniform<'a, T> {
-	pub fn pass(&self, data : &T) {
+	// in debug builds, fails if "T" doesn't match the type declared in the shader
+	#[track_caller]
+	pub fn pass(&self, data : &T) -> Result<(), GLError> {
+		if self.gl_type != 0 && !T::accepts(self.gl_type) {
+			return Err(GLError::MismatchedUniformType {
+				gl_type   : self.gl_type,
+				rust_type : std::any::type_name::<T>(),
+			});
+		}
 		unsafe {gl::UseProgram(self.program_id.0);}
-		data.pass(self)
+		data.pass(self);
+		check("Uniform::pass")
 	}
 }
 
 pub trait UniformData {
 	fn pass(&self, uniform : &Uniform<Self>) -> ();
+
+	// whether a uniform of GL type "gl_type" can be set from this type
+	fn accepts(_gl_type : GLenum) -> bool { true }
 }
 
 
+// types that also make arrays of uniforms, passed in a single glUniform*v call
+// "[T]" sets "count" elements of a GLSL array starting from the given location
+pub trait UniformElement : Sized {
+	fn pass_array(data : &[Self], location : GLint);
+	fn accepts(gl_type : GLenum) -> bool;
+}
 
-impl UniformData for M44 {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::UniformMatrix4fv(
-				uniform.location.0,
-				1,
-				gl::TRUE,
-				self.0.as_ptr().cast()
-			);
-		}
+impl<T : UniformElement> UniformData for T {
+	fn pass(&self, uniform : &Uniform<Self>) {
+		T::pass_array(std::slice::from_ref(self), uniform.location.0)
 	}
-}
 
+	fn accepts(gl_type : GLenum) -> bool { <T as UniformElement>::accepts(gl_type) }
+}
 
-impl UniformData for [M44] {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::UniformMatrix4fv(
-				uniform.location.0,
-				self.len() as gl::types::GLint,
-				gl::TRUE,
-				self.as_ptr().cast(),
-			);
+impl<T : UniformElement> UniformData for [T] {
+	fn pass(&self, uniform : &Uniform<Self>) {
+		if !self.is_empty() {
+			T::pass_array(self, uniform.location.0)
 		}
 	}
+
+	fn accepts(gl_type : GLenum) -> bool { <T as UniformElement>::accepts(gl_type) }
 }
 
-impl UniformData for V3 {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::Uniform3f(
-				uniform.location.0,
-				self.0[0],
-				self.0[1],
-				self.0[2],
-			);
-		}
+
+
+// -- SAMPLERS
+
+// texture unit read by a sampler uniform
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Sampler(pub GLint);
+
+impl UniformElement for Sampler {
+	fn pass_array(data : &[Self], location : GLint) {
+		unsafe {gl::Uniform1iv(location, data.len() as GLsizei, data.as_ptr().cast())}
 	}
+
+	fn accepts(gl_type : GLenum) -> bool { is_sampler(gl_type) }
 }
 
-impl UniformData for V2 {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::Uniform2f(
-				uniform.location.0,
-				self.0[0],
-				self.0[1],
-			);
-		}
-	}
+pub(crate) fn is_sampler(gl_type : GLenum) -> bool {
+	matches!(gl_type,
+		gl::SAMPLER_1D | gl::SAMPLER_2D | gl::SAMPLER_3D | gl::SAMPLER_CUBE |
+		gl::SAMPLER_1D_SHADOW | gl::SAMPLER_2D_SHADOW | gl::SAMPLER_CUBE_SHADOW |
+		gl::SAMPLER_1D_ARRAY | gl::SAMPLER_2D_ARRAY | gl::SAMPLER_CUBE_MAP_ARRAY |
+		gl::SAMPLER_1D_ARRAY_SHADOW | gl::SAMPLER_2D_ARRAY_SHADOW | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW |
+		gl::SAMPLER_2D_MULTISAMPLE | gl::SAMPLER_2D_MULTISAMPLE_ARRAY |
+		gl::SAMPLER_BUFFER | gl::SAMPLER_2D_RECT | gl::SAMPLER_2D_RECT_SHADOW |
+		gl::INT_SAMPLER_1D | gl::INT_SAMPLER_2D | gl::INT_SAMPLER_3D | gl::INT_SAMPLER_CUBE |
+		gl::INT_SAMPLER_1D_ARRAY | gl::INT_SAMPLER_2D_ARRAY | gl::INT_SAMPLER_CUBE_MAP_ARRAY |
+		gl::INT_SAMPLER_2D_MULTISAMPLE | gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY |
+		gl::INT_SAMPLER_BUFFER | gl::INT_SAMPLER_2D_RECT |
+		gl::UNSIGNED_INT_SAMPLER_1D | gl::UNSIGNED_INT_SAMPLER_2D | gl::UNSIGNED_INT_SAMPLER_3D |
+		gl::UNSIGNED_INT_SAMPLER_CUBE | gl::UNSIGNED_INT_SAMPLER_1D_ARRAY | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY |
+		gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE |
+		gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY | gl::UNSIGNED_INT_SAMPLER_BUFFER |
+		gl::UNSIGNED_INT_SAMPLER_2D_RECT
+	)
 }
 
-impl UniformData for f32 {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::Uniform1f(
-				uniform.location.0,
-				*self,
-			);
+
+
+// -- SCALARS & VECTORS
+// scalar doubles are passed as "[f64; 1]": an impl for f64 would make float literals, as in
+// "uniform.pass(&0.5)", infer to f64 rather than f32
+
+
+macro_rules! uniform_element {
+	// glUniform1i* also sets the texture unit of samplers
+	($rust_type:ty, $gl_fn:ident, $gl_elem:ty, samplers | $($gl_type:ident)|+) => {
+		impl UniformElement for $rust_type {
+			fn pass_array(data : &[Self], location : GLint) {
+				unsafe {gl::$gl_fn(location, data.len() as GLsizei, data.as_ptr() as *const $gl_elem)}
+			}
+
+			fn accepts(gl_type : GLenum) -> bool {
+				matches!(gl_type, $(gl::$gl_type)|+) || is_sampler(gl_type)
+			}
 		}
+	};
+	($rust_type:ty, $gl_fn:ident, $gl_elem:ty, $($gl_type:ident)|+) => {
+		impl UniformElement for $rust_type {
+			fn pass_array(data : &[Self], location : GLint) {
+				unsafe {gl::$gl_fn(location, data.len() as GLsizei, data.as_ptr() as *const $gl_elem)}
+			}
+
+			fn accepts(gl_type : GLenum) -> bool {
+				matches!(gl_type, $(gl::$gl_type)|+)
+			}
+		}
+	};
+}
+
+uniform_element!(f32,        Uniform1fv,  GLfloat,  FLOAT);
+uniform_element!(GLint,      Uniform1iv,  GLint,    samplers | INT | BOOL);
+uniform_element!(GLuint,     Uniform1uiv, GLuint,   UNSIGNED_INT | BOOL);
+
+uniform_element!(V2,         Uniform2fv,  GLfloat,  FLOAT_VEC2);
+uniform_element!(V3,         Uniform3fv,  GLfloat,  FLOAT_VEC3);
+uniform_element!(V4,         Uniform4fv,  GLfloat,  FLOAT_VEC4);
+
+uniform_element!([f32; 1],   Uniform1fv,  GLfloat,  FLOAT);
+uniform_element!([f32; 2],   Uniform2fv,  GLfloat,  FLOAT_VEC2);
+uniform_element!([f32; 3],   Uniform3fv,  GLfloat,  FLOAT_VEC3);
+uniform_element!([f32; 4],   Uniform4fv,  GLfloat,  FLOAT_VEC4);
+
+uniform_element!([f64; 1],   Uniform1dv,  GLdouble, DOUBLE);
+uniform_element!([f64; 2],   Uniform2dv,  GLdouble, DOUBLE_VEC2);
+uniform_element!([f64; 3],   Uniform3dv,  GLdouble, DOUBLE_VEC3);
+uniform_element!([f64; 4],   Uniform4dv,  GLdouble, DOUBLE_VEC4);
+
+uniform_element!([GLint; 1], Uniform1iv,  GLint,    samplers | INT | BOOL);
+uniform_element!([GLint; 2], Uniform2iv,  GLint,    INT_VEC2 | BOOL_VEC2);
+uniform_element!([GLint; 3], Uniform3iv,  GLint,    INT_VEC3 | BOOL_VEC3);
+uniform_element!([GLint; 4], Uniform4iv,  GLint,    INT_VEC4 | BOOL_VEC4);
+
+uniform_element!([GLuint; 1], Uniform1uiv, GLuint,  UNSIGNED_INT | BOOL);
+uniform_element!([GLuint; 2], Uniform2uiv, GLuint,  UNSIGNED_INT_VEC2 | BOOL_VEC2);
+uniform_element!([GLuint; 3], Uniform3uiv, GLuint,  UNSIGNED_INT_VEC3 | BOOL_VEC3);
+uniform_element!([GLuint; 4], Uniform4uiv, GLuint,  UNSIGNED_INT_VEC4 | BOOL_VEC4);
+
+
+// bools are passed as ints, hence a conversion
+impl UniformElement for bool {
+	fn pass_array(data : &[Self], location : GLint) {
+		let ints : Vec<GLint> = data.iter().map(|elem| GLint::from(*elem)).collect();
+		unsafe {gl::Uniform1iv(location, ints.len() as GLsizei, ints.as_ptr())}
 	}
+
+	fn accepts(gl_type : GLenum) -> bool { gl_type == gl::BOOL }
 }
 
-impl UniformData for gl::types::GLuint {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::Uniform1ui(
-				uniform.location.0,
-				*self,
-			);
+macro_rules! uniform_bool_vector {
+	($n:expr, $gl_fn:ident, $gl_type:ident) => {
+		impl UniformElement for [bool; $n] {
+			fn pass_array(data : &[Self], location : GLint) {
+				let ints : Vec<GLint> = data.iter().flatten().map(|elem| GLint::from(*elem)).collect();
+				unsafe {gl::$gl_fn(location, data.len() as GLsizei, ints.as_ptr())}
+			}
+
+			fn accepts(gl_type : GLenum) -> bool { gl_type == gl::$gl_type }
 		}
-	}
+	};
 }
 
-impl UniformData for gl::types::GLint {
-	fn pass(&self, uniform : &Uniform<Self>) -> () {
-		unsafe {
-			gl::Uniform1i(
-				uniform.location.0,
-				*self,
-			);
+uniform_bool_vector!(1, Uniform1iv, BOOL);
+uniform_bool_vector!(2, Uniform2iv, BOOL_VEC2);
+uniform_bool_vector!(3, Uniform3iv, BOOL_VEC3);
+uniform_bool_vector!(4, Uniform4iv, BOOL_VEC4);
+
+
+
+// -- MATRICES
+// row-major, like "Matrix": "[[T; C]; R]" has R rows of C elements, that is a GLSL "matCxR"
+
+
+macro_rules! uniform_matrix {
+	($rust_type:ty, $gl_fn:ident, $gl_elem:ty, $gl_type:ident) => {
+		impl UniformElement for $rust_type {
+			fn pass_array(data : &[Self], location : GLint) {
+				unsafe {gl::$gl_fn(location, data.len() as GLsizei, gl::TRUE, data.as_ptr() as *const $gl_elem)}
+			}
+
+			fn accepts(gl_type : GLenum) -> bool { gl_type == gl::$gl_type }
 		}
+	};
+}
+
+uniform_matrix!(M22,            UniformMatrix2fv,   GLfloat,  FLOAT_MAT2);
+uniform_matrix!(M33,            UniformMatrix3fv,   GLfloat,  FLOAT_MAT3);
+uniform_matrix!(M44,            UniformMatrix4fv,   GLfloat,  FLOAT_MAT4);
+
+uniform_matrix!([[f32; 2]; 2],  UniformMatrix2fv,   GLfloat,  FLOAT_MAT2);
+uniform_matrix!([[f32; 3]; 3],  UniformMatrix3fv,   GLfloat,  FLOAT_MAT3);
+uniform_matrix!([[f32; 4]; 4],  UniformMatrix4fv,   GLfloat,  FLOAT_MAT4);
+uniform_matrix!([[f32; 2]; 3],  UniformMatrix2x3fv, GLfloat,  FLOAT_MAT2x3);
+uniform_matrix!([[f32; 2]; 4],  UniformMatrix2x4fv, GLfloat,  FLOAT_MAT2x4);
+uniform_matrix!([[f32; 3]; 2],  UniformMatrix3x2fv, GLfloat,  FLOAT_MAT3x2);
+uniform_matrix!([[f32; 3]; 4],  UniformMatrix3x4fv, GLfloat,  FLOAT_MAT3x4);
+uniform_matrix!([[f32; 4]; 2],  UniformMatrix4x2fv, GLfloat,  FLOAT_MAT4x2);
+uniform_matrix!([[f32; 4]; 3],  UniformMatrix4x3fv, GLfloat,  FLOAT_MAT4x3);
+
+uniform_matrix!([[f64; 2]; 2],  UniformMatrix2dv,   GLdouble, DOUBLE_MAT2);
+uniform_matrix!([[f64; 3]; 3],  UniformMatrix3dv,   GLdouble, DOUBLE_MAT3);
+uniform_matrix!([[f64; 4]; 4],  UniformMatrix4dv,   GLdouble, DOUBLE_MAT4);
+uniform_matrix!([[f64; 2]; 3],  UniformMatrix2x3dv, GLdouble, DOUBLE_MAT2x3);
+uniform_matrix!([[f64; 2]; 4],  UniformMatrix2x4dv, GLdouble, DOUBLE_MAT2x4);
+uniform_matrix!([[f64; 3]; 2],  UniformMatrix3x2dv, GLdouble, DOUBLE_MAT3x2);
+uniform_matrix!([[f64; 3]; 4],  UniformMatrix3x4dv, GLdouble, DOUBLE_MAT3x4);
+uniform_matrix!([[f64; 4]; 2],  UniformMatrix4x2dv, GLdouble, DOUBLE_MAT4x2);
+uniform_matrix!([[f64; 4]; 3],  UniformMatrix4x3dv, GLdouble, DOUBLE_MAT4x3);
+
+
+
+// -- TESTS
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn accepted_gl_types() {
+		assert!(<M44 as UniformData>::accepts(gl::FLOAT_MAT4));
+		assert!(!<M44 as UniformData>::accepts(gl::FLOAT_MAT3));
+		assert!(<[M44] as UniformData>::accepts(gl::FLOAT_MAT4));
+		assert!(<GLint as UniformData>::accepts(gl::BOOL));
+		assert!(<GLint as UniformData>::accepts(gl::SAMPLER_2D));
+		assert!(<[GLint] as UniformData>::accepts(gl::INT_SAMPLER_3D));
+		assert!(!<GLuint as UniformData>::accepts(gl::SAMPLER_2D));
+		assert!(<Sampler as UniformData>::accepts(gl::UNSIGNED_INT_SAMPLER_2D_ARRAY));
+		assert!(<[[f32; 3]; 2] as UniformData>::accepts(gl::FLOAT_MAT3x2));
+		assert!(<[bool; 3] as UniformData>::accepts(gl::BOOL_VEC3));
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn mismatched_types_are_errors() {
+		let program_id = ProgramId(1);
+		let uniform = Uniform::<f32> {
+			program_id : &program_id,
+			location   : LayoutLocation(0),
+			gl_type    : gl::FLOAT_VEC3,
+			phantom    : std::marker::PhantomData,
+		};
+		let passed = uniform.pass(&1.0);
+		assert!(matches!(passed, Err(GLError::MismatchedUniformType { gl_type : gl::FLOAT_VEC3, .. })));
+	}
+}
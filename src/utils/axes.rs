@@ -43,11 +43,11 @@ impl AxesBuilder {
 			.build()?
 		;
 
-		program.bind("position", buffer.direct_view())?;
+		program.bind("position", buffer.direct_view()?)?;
 
 		let model = M44::id();
 
-		program.uniform("model").unwrap().pass(&model);
+		program.uniform("model")?.pass(&model)?;
 
 		Ok(Self {
 			// buffer,
@@ -79,8 +79,8 @@ impl<'a> Axes<'a> {
 
 	pub fn draw(&self, vp : &M44) -> Result<(), GLError> {
 		self.builder.program.set_current();
-		self.vp_uniform.pass(vp);
-		self.model_uniform.pass(&self.model);
+		self.vp_uniform.pass(vp)?;
+		self.model_uniform.pass(&self.model)?;
 		self.builder.program.draw_buffer(DrawMode::Lines)?;
 		Ok(())
 	}